@@ -0,0 +1,24 @@
+#[cfg(feature = "tokio")]
+use rust_async_examples::runtime::TokioRuntime;
+#[cfg(feature = "tokio")]
+use rust_async_examples::tasks::http::{Router, Server};
+
+// Serves the current directory, so `index.html` is the start page
+#[cfg(feature = "tokio")]
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let router = Router::new().static_dir::<TokioRuntime>("/", ".");
+    let server = Server::<TokioRuntime>::bind("127.0.0.1:8000".parse().unwrap(), router).await?;
+    let shutdown = server.shutdown_handle();
+    println!("Slides at http://{}/, Ctrl-C to stop", server.local_addr()?);
+    let running = tokio::spawn(server.run());
+
+    tokio::signal::ctrl_c().await?;
+    shutdown.shutdown();
+    running.await.expect("server task panicked")
+}
+
+#[cfg(not(feature = "tokio"))]
+fn main() {
+    panic!("tokio feature needed: cargo run --example serve_slides --features tokio");
+}
@@ -0,0 +1,36 @@
+use rust_async_examples::simple_http_client::SimpleHttpClient;
+use std::thread;
+
+// Fetches a URL from several plain threads with the blocking client; no
+// async runtime involved. Pass a URL to fetch something else.
+fn main() {
+    let url = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "http://httpbin.org/get".to_string());
+    let client = SimpleHttpClient::blocking();
+    println!("Main thread: fetching {} from 3 threads", url);
+
+    let handles: Vec<_> = (1..=3)
+        .map(|i| {
+            let (client, url) = (client.clone(), url.clone());
+            thread::spawn(move || {
+                println!("  thread_{}: started", i);
+                match client.get(&url) {
+                    Ok(response) => println!(
+                        "  thread_{}: {} {} ({} bytes)",
+                        i,
+                        response.status,
+                        response.reason,
+                        response.body.len()
+                    ),
+                    Err(e) => println!("  thread_{}: failed: {}", i, e),
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    println!("Main thread: all threads finished");
+}
@@ -17,7 +17,7 @@ where
             println!("{} tries to get the food...", animal);
             match food.lock() {
                 Ok(mut food) => {
-                    action(&mut *food);
+                    action(&mut food);
                     println!("{} is done with the food.", animal);
                 }
                 Err(poisoned) => {
@@ -29,7 +29,7 @@ where
                 }
             }
         })
-        .expect(&format!("Failed to spawn thread: {}", thread_name))
+        .unwrap_or_else(|e| panic!("Failed to spawn thread: {}: {}", thread_name, e))
 }
 
 fn main() {
@@ -38,7 +38,7 @@ async fn main() {
 
         // Spawn Bear (child of Mammal)
         let bear = tokio::spawn(async {
-            let bear_start = Instant::now();
+            let _bear_start = Instant::now();
             println!("    🐻 Bear: 🚀 started (child of Mammal)");
 
             // Fruits as sub-tasks (children of Bear)
@@ -1,13 +1,20 @@
 #[cfg(feature = "tokio")]
-use rust_async_examples::simple_http_client::SimpleHttpClient;
+use rust_async_examples::runtime::TokioRuntime;
+#[cfg(feature = "tokio")]
+use rust_async_examples::simple_http_client::{HttpError, SimpleHttpClient};
+#[cfg(feature = "tokio")]
+use rust_async_examples::tasks::http::HttpBin;
 
 #[cfg(feature = "tokio")]
 #[tokio::main]
-async fn main() -> Result<(), std::io::Error> {
+async fn main() -> Result<(), HttpError> {
+    // Posts to the local httpbin look-alike, so the example runs offline
+    let httpbin = HttpBin::start::<TokioRuntime>().await?;
     let test_body = "Hello from World!";
-    let response = SimpleHttpClient::post("http://httpbin.org/post", test_body).await?;
-    println!("POST response: {}", response);
-    assert!(response.contains(test_body));
+    let response = SimpleHttpClient::post(&httpbin.url("/post"), test_body).await?;
+    println!("POST response: {}", response.text()?);
+    assert_eq!(response.status, 200);
+    assert!(response.text()?.contains(test_body));
     Ok(())
 }
 
@@ -0,0 +1,39 @@
+#[cfg(feature = "tokio")]
+use rust_async_examples::runtime::TokioRuntime;
+#[cfg(feature = "tokio")]
+use rust_async_examples::simple_http_client::SimpleHttpClient;
+#[cfg(feature = "tokio")]
+use rust_async_examples::tasks::http::{Request, Response, Router, Server};
+
+#[cfg(feature = "tokio")]
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let router = Router::new()
+        .get("/hello/{name}", |request: Request| async move {
+            Response::text(format!("Hello, {}!\n", request.param("name").unwrap_or("")))
+        })
+        .post("/echo", |request: Request| async move {
+            Response::new(200).body(request.body)
+        });
+    let server = Server::<TokioRuntime>::bind("127.0.0.1:8080".parse().unwrap(), router).await?;
+    let addr = server.local_addr()?;
+    let shutdown = server.shutdown_handle();
+    let running = tokio::spawn(server.run());
+
+    // The client half talking to the server half
+    let response = SimpleHttpClient::get(&format!("http://{}/hello/world", addr)).await?;
+    print!("GET /hello/world: {}", response.text()?);
+
+    println!("Listening on http://{}, Ctrl-C to stop", addr);
+    tokio::signal::ctrl_c().await?;
+    // Stop accepting and let requests in flight finish
+    shutdown.shutdown();
+    running.await.expect("server task panicked")?;
+    println!("Server stopped");
+    Ok(())
+}
+
+#[cfg(not(feature = "tokio"))]
+fn main() {
+    panic!("tokio feature needed: cargo run --example tokio_http_server --features tokio");
+}
@@ -1,3 +1,4 @@
+pub mod runtime;
 pub mod simple_http_client;
 pub mod tasks;
 
@@ -0,0 +1,426 @@
+use futures_util::future::{select, Either};
+use std::future::Future;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A connected byte stream, e.g. a TCP socket of one of the async runtimes.
+// The methods mirror the handful of AsyncReadExt/AsyncWriteExt calls the HTTP
+// code needs, so it can be written once for every runtime.
+pub trait Transport: Send + Unpin + 'static {
+    // Reads into `buf`, returning the number of bytes read (0 means EOF)
+    fn read(&mut self, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send;
+
+    fn write_all(&mut self, buf: &[u8]) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn flush(&mut self) -> impl Future<Output = io::Result<()>> + Send;
+
+    // Bounds how long a single read or write may block. Only transports
+    // whose operations block the thread need this; the futures of async
+    // transports are bounded by dropping them.
+    fn set_timeout(&mut self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Whether `e` reports an expired `Transport::set_timeout`. Unix reports a
+// read past `set_read_timeout` as WouldBlock, Windows as TimedOut.
+pub(crate) fn is_socket_timeout(e: &io::Error) -> bool {
+    let kind = e.kind();
+    e.get_ref().is_none() && matches!(kind, io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+// A bound TCP listener of one of the runtimes
+pub trait Listener: Send + Sync + 'static {
+    type Stream: Transport;
+
+    fn accept(&self) -> impl Future<Output = io::Result<(Self::Stream, SocketAddr)>> + Send;
+
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+}
+
+// The operations the crate needs from an async runtime. Implementations are
+// zero-sized marker types, selected as a type parameter.
+pub trait Runtime: Send + Sync + 'static {
+    type Stream: Transport;
+    type Listener: Listener<Stream = Self::Stream>;
+
+    // Connects to `addr`. `timeout` bounds how long the connect may block;
+    // like `Transport::set_timeout` only runtimes whose connects block the
+    // thread need it, async connects are bounded by dropping the future.
+    fn connect(
+        addr: SocketAddr,
+        timeout: Option<Duration>,
+    ) -> impl Future<Output = io::Result<Self::Stream>> + Send;
+
+    // Looks up every address of `host`, in the order the resolver returns
+    fn resolve(host: &str, port: u16) -> impl Future<Output = io::Result<Vec<SocketAddr>>> + Send;
+
+    fn bind(addr: SocketAddr) -> impl Future<Output = io::Result<Self::Listener>> + Send;
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+
+    // Runs `future` in the background; its result is discarded
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static;
+
+    // Runs blocking code such as file I/O where it cannot stall other tasks
+    fn spawn_blocking<F, T>(f: F) -> impl Future<Output = T> + Send
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static;
+}
+
+// Runs `future` to completion unless `duration` elapses first, in which case
+// `None` is returned and the future is dropped. Works on any `Runtime`.
+pub async fn timeout<R: Runtime, F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    let future = pin!(future);
+    let sleep = pin!(R::sleep(duration));
+    match select(future, sleep).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+// The runtime used when none is named explicitly. Tokio wins when both the
+// `tokio` and `smol` features are enabled; smol stays usable by naming
+// `SmolRuntime` explicitly. Without either, the blocking `StdRuntime` is used.
+#[cfg(feature = "tokio")]
+pub type DefaultRuntime = TokioRuntime;
+
+#[cfg(all(feature = "smol", not(feature = "tokio")))]
+pub type DefaultRuntime = SmolRuntime;
+
+#[cfg(not(any(feature = "tokio", feature = "smol")))]
+pub type DefaultRuntime = StdRuntime;
+
+#[cfg(feature = "tokio")]
+pub struct TokioRuntime;
+
+#[cfg(feature = "tokio")]
+impl Transport for tokio::net::TcpStream {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        tokio::io::AsyncReadExt::read(self, buf).await
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        tokio::io::AsyncWriteExt::write_all(self, buf).await
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        tokio::io::AsyncWriteExt::flush(self).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Listener for tokio::net::TcpListener {
+    type Stream = tokio::net::TcpStream;
+
+    async fn accept(&self) -> io::Result<(Self::Stream, SocketAddr)> {
+        tokio::net::TcpListener::accept(self).await
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        tokio::net::TcpListener::local_addr(self)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Runtime for TokioRuntime {
+    type Stream = tokio::net::TcpStream;
+    type Listener = tokio::net::TcpListener;
+
+    async fn connect(addr: SocketAddr, _timeout: Option<Duration>) -> io::Result<Self::Stream> {
+        tokio::net::TcpStream::connect(addr).await
+    }
+
+    async fn resolve(host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        Ok(tokio::net::lookup_host((host, port)).await?.collect())
+    }
+
+    async fn bind(addr: SocketAddr) -> io::Result<Self::Listener> {
+        tokio::net::TcpListener::bind(addr).await
+    }
+
+    async fn sleep(duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // Dropping the JoinHandle detaches the task
+        tokio::spawn(future);
+    }
+
+    async fn spawn_blocking<F, T>(f: F) -> T
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        match tokio::task::spawn_blocking(f).await {
+            Ok(output) => output,
+            Err(e) => match e.try_into_panic() {
+                Ok(panic) => std::panic::resume_unwind(panic),
+                Err(e) => panic!("blocking task failed: {}", e),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "smol")]
+pub struct SmolRuntime;
+
+#[cfg(feature = "smol")]
+impl Transport for smol::net::TcpStream {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        smol::io::AsyncReadExt::read(self, buf).await
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        smol::io::AsyncWriteExt::write_all(self, buf).await
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        smol::io::AsyncWriteExt::flush(self).await
+    }
+}
+
+#[cfg(feature = "smol")]
+impl Listener for smol::net::TcpListener {
+    type Stream = smol::net::TcpStream;
+
+    async fn accept(&self) -> io::Result<(Self::Stream, SocketAddr)> {
+        smol::net::TcpListener::accept(self).await
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        smol::net::TcpListener::local_addr(self)
+    }
+}
+
+#[cfg(feature = "smol")]
+impl Runtime for SmolRuntime {
+    type Stream = smol::net::TcpStream;
+    type Listener = smol::net::TcpListener;
+
+    async fn connect(addr: SocketAddr, _timeout: Option<Duration>) -> io::Result<Self::Stream> {
+        smol::net::TcpStream::connect(addr).await
+    }
+
+    async fn resolve(host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        smol::net::resolve((host, port)).await
+    }
+
+    async fn bind(addr: SocketAddr) -> io::Result<Self::Listener> {
+        smol::net::TcpListener::bind(addr).await
+    }
+
+    async fn sleep(duration: Duration) {
+        smol::Timer::after(duration).await;
+    }
+
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // A smol Task is cancelled when dropped, so detach it explicitly
+        smol::spawn(future).detach();
+    }
+
+    async fn spawn_blocking<F, T>(f: F) -> T
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        smol::unblock(f).await
+    }
+}
+
+// Blocking runtime on top of `std::net`, available in every build and the
+// default when neither `tokio` nor `smol` is enabled. Its futures block the
+// calling thread, so drive them with `block_on` from a plain thread, not
+// from inside another runtime. Timers run on helper threads.
+pub struct StdRuntime;
+
+impl Transport for std::net::TcpStream {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io::Read::read(self, buf)
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        io::Write::write_all(self, buf)
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        io::Write::flush(self)
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        // A zero timeout is rejected by the OS APIs
+        let timeout = timeout.map(|timeout| timeout.max(Duration::from_millis(1)));
+        self.set_read_timeout(timeout)?;
+        self.set_write_timeout(timeout)
+    }
+}
+
+// How often a `StdRuntime` listener checks for a new connection. Between
+// checks the task yields, so e.g. an aborted server stops accepting.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+// `StdRuntime::bind` makes the listener non-blocking, so waiting for a
+// connection cannot stall its task indefinitely
+impl Listener for std::net::TcpListener {
+    type Stream = std::net::TcpStream;
+
+    async fn accept(&self) -> io::Result<(Self::Stream, SocketAddr)> {
+        loop {
+            match std::net::TcpListener::accept(self) {
+                Ok((stream, peer)) => {
+                    // Some platforms pass the listener's mode on
+                    stream.set_nonblocking(false)?;
+                    return Ok((stream, peer));
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(ACCEPT_POLL_INTERVAL);
+                    YieldNow(false).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        std::net::TcpListener::local_addr(self)
+    }
+}
+
+impl Runtime for StdRuntime {
+    type Stream = std::net::TcpStream;
+    type Listener = std::net::TcpListener;
+
+    async fn connect(addr: SocketAddr, timeout: Option<Duration>) -> io::Result<Self::Stream> {
+        match timeout {
+            // A zero timeout is rejected by the OS APIs
+            Some(timeout) => {
+                std::net::TcpStream::connect_timeout(&addr, timeout.max(Duration::from_millis(1)))
+            }
+            None => std::net::TcpStream::connect(addr),
+        }
+    }
+
+    async fn resolve(host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        Ok((host, port).to_socket_addrs()?.collect())
+    }
+
+    async fn bind(addr: SocketAddr) -> io::Result<Self::Listener> {
+        let listener = std::net::TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(listener)
+    }
+
+    async fn sleep(duration: Duration) {
+        ThreadTimer::new(duration).await
+    }
+
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        thread::spawn(move || block_on(future));
+    }
+
+    async fn spawn_blocking<F, T>(f: F) -> T
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        // Every StdRuntime task already has a thread to itself
+        f()
+    }
+}
+
+// Runs `future` to completion on the current thread, parking the thread
+// while the future waits. Meant for `StdRuntime` futures.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+// The timer of `StdRuntime`. A helper thread sleeps until the deadline and
+// then wakes the task, so other futures polled together with the timer,
+// e.g. by `timeout`, keep making progress.
+struct ThreadTimer {
+    deadline: Instant,
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+impl ThreadTimer {
+    fn new(duration: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + duration,
+            waker: None,
+        }
+    }
+}
+
+impl Future for ThreadTimer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            Some(waker) => waker.lock().unwrap().clone_from(cx.waker()),
+            None => {
+                let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                let (deadline, shared) = (self.deadline, waker.clone());
+                thread::spawn(move || {
+                    thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                    shared.lock().unwrap().wake_by_ref();
+                });
+                self.waker = Some(waker);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+// Returns to the executor once, with the task already woken, so that the
+// futures wrapping it, e.g. `Abortable`, get to run before it continues
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -0,0 +1,46 @@
+use super::client::{ClientConfig, HttpClient};
+use super::error::HttpError;
+use super::request::{Method, RequestBuilder};
+use super::response::HttpResponse;
+use crate::runtime::{block_on, StdRuntime};
+
+// An HTTP client for plain threads, built on `std::net` and usable without
+// any async runtime feature. Every call blocks the calling thread until the
+// response has been read. Like `HttpClient` it pools keep-alive connections;
+// clones share the pool, so one client can serve many threads.
+#[derive(Clone, Default)]
+pub struct BlockingClient {
+    client: HttpClient<StdRuntime>,
+}
+
+impl BlockingClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: ClientConfig) -> Self {
+        Self {
+            client: HttpClient::with_config(config),
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Result<HttpResponse, HttpError> {
+        block_on(self.client.get(url))
+    }
+
+    pub fn post(&self, url: &str, body: &str) -> Result<HttpResponse, HttpError> {
+        block_on(self.client.post(url, body))
+    }
+
+    // Starts building a request; finish it with `send_blocking`
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder<StdRuntime> {
+        self.client.request(method, url)
+    }
+}
+
+impl RequestBuilder<StdRuntime> {
+    // Sends the request and reads the whole response, blocking the thread
+    pub fn send_blocking(self) -> Result<HttpResponse, HttpError> {
+        block_on(self.send())
+    }
+}
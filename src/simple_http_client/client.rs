@@ -0,0 +1,429 @@
+use super::connector;
+use super::error::{HttpError, TimeoutKind};
+use super::pool::{Pool, PoolConfig, PoolKey};
+use super::reader::{BodyReader, Connection, Head};
+use super::redirect::{self, RedirectPolicy};
+use super::request::{Method, Request, RequestBuilder};
+use super::resolver::Resolver;
+use super::response::HttpResponse;
+use super::retry::RetryPolicy;
+use super::stream::{ClientStream, Limit};
+use super::streaming::{BodyStream, StreamingResponse};
+use super::target::parse_url;
+#[cfg(feature = "tls")]
+use super::tls::{TlsConfig, TlsStream};
+use crate::runtime::{DefaultRuntime, Runtime, Transport};
+use async_lock::SemaphoreGuardArc;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use url::Url;
+
+// Settings shared by every request made through an `HttpClient`
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    pub pool: PoolConfig,
+    pub timeouts: Timeouts,
+    pub redirect: RedirectPolicy,
+    pub retry: RetryPolicy,
+    // Largest response body `send` reads into memory; bodies of streaming
+    // responses are not limited
+    pub max_body_size: Option<usize>,
+    // Looks up host names; `None` uses the runtime's system resolver
+    pub resolver: Option<Arc<dyn Resolver>>,
+    // Root certificates trusted for `https` URLs
+    #[cfg(feature = "tls")]
+    pub tls: TlsConfig,
+}
+
+// Time limits for a request. `None` means no limit. Requests can override
+// the client's defaults with `RequestBuilder::timeout` and friends. An
+// expired limit fails the request with `HttpError::Timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    // Establishing the connection, including the TLS handshake
+    pub connect: Option<Duration>,
+    // Longest wait for any single socket read or write, i.e. how long the
+    // server may stay silent
+    pub read: Option<Duration>,
+    // The whole request, from waiting for a pool slot to the end of the body,
+    // including retries and the delays between them
+    pub total: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Some(Duration::from_secs(30)),
+            read: None,
+            total: None,
+        }
+    }
+}
+
+impl Timeouts {
+    // Fills every limit not set in `self` from `defaults`
+    pub(crate) fn or(self, defaults: Timeouts) -> Timeouts {
+        Timeouts {
+            connect: self.connect.or(defaults.connect),
+            read: self.read.or(defaults.read),
+            total: self.total.or(defaults.total),
+        }
+    }
+}
+
+// An HTTP client that keeps connections alive and reuses them across
+// requests. Cloning is cheap and clones share the same connection pool.
+pub struct HttpClient<R: Runtime = DefaultRuntime> {
+    pool: Arc<Pool<ClientStream<R>>>,
+    timeouts: Timeouts,
+    redirect: RedirectPolicy,
+    retry: Arc<RetryPolicy>,
+    max_body_size: Option<usize>,
+    resolver: Option<Arc<dyn Resolver>>,
+    #[cfg(feature = "tls")]
+    tls: Arc<rustls::ClientConfig>,
+    _runtime: PhantomData<R>,
+}
+
+impl HttpClient {
+    // Creates a client on the default runtime with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<R: Runtime> Default for HttpClient<R> {
+    fn default() -> Self {
+        Self::with_config(ClientConfig::default())
+    }
+}
+
+impl<R: Runtime> Clone for HttpClient<R> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            timeouts: self.timeouts,
+            redirect: self.redirect,
+            retry: self.retry.clone(),
+            max_body_size: self.max_body_size,
+            resolver: self.resolver.clone(),
+            #[cfg(feature = "tls")]
+            tls: self.tls.clone(),
+            _runtime: PhantomData,
+        }
+    }
+}
+
+impl<R: Runtime> HttpClient<R> {
+    // Creates a client on runtime `R`,
+    // e.g. `HttpClient::<SmolRuntime>::with_config(config)`
+    pub fn with_config(config: ClientConfig) -> Self {
+        Self {
+            pool: Arc::new(Pool::new(config.pool)),
+            timeouts: config.timeouts,
+            redirect: config.redirect,
+            retry: Arc::new(config.retry),
+            max_body_size: config.max_body_size,
+            resolver: config.resolver,
+            #[cfg(feature = "tls")]
+            tls: config.tls.client_config(),
+            _runtime: PhantomData,
+        }
+    }
+
+    // Starts building a request with any method
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder<R> {
+        RequestBuilder::new(self.clone(), method, url)
+    }
+
+    pub async fn get(&self, url: &str) -> Result<HttpResponse, HttpError> {
+        self.request(Method::Get, url).send().await
+    }
+
+    pub async fn post(&self, url: &str, body: &str) -> Result<HttpResponse, HttpError> {
+        self.request(Method::Post, url)
+            .content_type("text/plain")
+            .body(body)
+            .send()
+            .await
+    }
+
+    // Number of idle connections currently pooled for the origin of `url`
+    pub fn idle_connections(&self, url: &str) -> Result<usize, HttpError> {
+        let key = pool_key(&parse_url(url)?)?;
+        Ok(self.pool.idle_count(&key))
+    }
+
+    pub(crate) async fn execute(&self, request: Request) -> Result<HttpResponse, HttpError> {
+        let mut exchange = self.open_with_retries(request).await?;
+        let mut response = exchange
+            .conn
+            .read_to_end(exchange.head, exchange.method, self.max_body_size)
+            .await?;
+        response.redirects = exchange.redirects;
+        self.pool.checkin(&exchange.key, exchange.conn);
+        Ok(response)
+    }
+
+    pub(crate) async fn execute_streaming(
+        &self,
+        request: Request,
+    ) -> Result<StreamingResponse, HttpError> {
+        let Exchange {
+            conn,
+            head,
+            key,
+            permit,
+            method,
+            redirects,
+        } = self.open_with_retries(request).await?;
+        let reader = BodyReader::new(&head, method)?;
+        let pool = self.pool.clone();
+        let body = BodyStream::new(conn, reader, move |conn| {
+            pool.checkin(&key, conn);
+            drop(permit);
+        });
+        let mut response = StreamingResponse::new(head, body);
+        response.redirects = redirects;
+        Ok(response)
+    }
+
+    // Opens the exchange for `request`, repeating it as the retry policy
+    // allows while it fails or answers with a retryable status. All attempts
+    // and the delays between them share one request deadline.
+    async fn open_with_retries(&self, request: Request) -> Result<Exchange<R>, HttpError> {
+        let policy = match &request.retry {
+            Some(policy) => policy,
+            None => &*self.retry,
+        };
+        let timeouts = request.timeouts.or(self.timeouts);
+        let deadline = timeouts.total.map(|total| Instant::now() + total);
+        let mut attempt = 1;
+        loop {
+            let result = self.open(request.clone(), timeouts, deadline, policy).await;
+            let retryable = match &result {
+                Ok(exchange) => policy.retry_on_status.contains(&exchange.head.status),
+                Err(e) => policy.retries_error(e),
+            };
+            if !retryable || attempt >= policy.max_attempts || !policy.allows(request.method) {
+                return result;
+            }
+            let backoff = policy.backoff(attempt);
+            // Another attempt could not finish before the deadline
+            if let (Some(deadline), Some(total)) = (deadline, timeouts.total) {
+                if deadline.saturating_duration_since(Instant::now()) <= backoff {
+                    return Err(HttpError::Timeout {
+                        kind: TimeoutKind::Total,
+                        after: total,
+                    });
+                }
+            }
+            if let Ok(exchange) = result {
+                self.discard(exchange).await;
+            }
+            R::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    // Reads and drops the body of a response nobody wants, so that its
+    // connection can be reused
+    async fn discard(&self, mut exchange: Exchange<R>) {
+        let body = exchange
+            .conn
+            .read_to_end(exchange.head, exchange.method, self.max_body_size);
+        if body.await.is_ok() {
+            self.pool.checkin(&exchange.key, exchange.conn);
+        }
+    }
+
+    // Sends `request` and follows redirects as far as the policy allows,
+    // returning the exchange whose response head is final
+    async fn open(
+        &self,
+        mut request: Request,
+        timeouts: Timeouts,
+        deadline: Option<Instant>,
+        retry: &RetryPolicy,
+    ) -> Result<Exchange<R>, HttpError> {
+        let policy = request.redirect.unwrap_or(self.redirect);
+        let mut redirects = Vec::new();
+
+        loop {
+            let (mut conn, head, key, permit) =
+                self.send(&request, timeouts, deadline, retry).await?;
+            let next = if policy.follows() {
+                redirect::next_request(&request, &head)?
+            } else {
+                None
+            };
+            let Some(next) = next else {
+                return Ok(Exchange {
+                    conn,
+                    head,
+                    key,
+                    permit,
+                    method: request.method,
+                    redirects,
+                });
+            };
+            if redirects.len() == policy.max_redirects() {
+                return Err(HttpError::TooManyRedirects {
+                    limit: policy.max_redirects(),
+                });
+            }
+            // Read the redirect's body so the connection can be reused
+            conn.read_to_end(head, request.method, self.max_body_size)
+                .await?;
+            self.pool.checkin(&key, conn);
+            drop(permit);
+            redirects.push(request.url);
+            request = next;
+        }
+    }
+
+    // Sends `request` on a pooled or new connection and reads the response
+    // head. The permit must be held until the body has been read.
+    async fn send(
+        &self,
+        request: &Request,
+        timeouts: Timeouts,
+        deadline: Option<Instant>,
+        retry: &RetryPolicy,
+    ) -> Result<OpenExchange<R>, HttpError> {
+        let key = pool_key(&request.url)?;
+        let wire = request.to_bytes();
+
+        let (permit, idle) = Limit::new(None, TimeoutKind::Total, deadline)
+            .run::<R, _>(async { Ok(self.pool.checkout(&key).await) })
+            .await?;
+
+        if let Some(mut conn) = idle {
+            conn.get_mut().set_timeouts(timeouts.read, deadline);
+            match conn.send_request(&wire).await {
+                // The server closed the idle connection before we used it and
+                // never saw the whole request, so send it on a new connection
+                Err(e) if is_stale_connection(&e) => {}
+                Err(e) => return Err(e.into()),
+                Ok(()) => match conn.read_head().await {
+                    Ok(head) => return Ok((conn, head, key, permit)),
+                    // The connection closed after the request was written, so
+                    // the server may have acted on it. Only requests that are
+                    // safe to repeat go out again.
+                    Err(e)
+                        if !conn.has_received_response()
+                            && is_stale_connection(&e)
+                            && retry.allows(request.method) => {}
+                    Err(e) => return Err(e.into()),
+                },
+            }
+        }
+
+        let limit = Limit::new(timeouts.connect, TimeoutKind::Connect, deadline);
+        let connect = self.connect(&key, limit.duration());
+        let stream = limit.run::<R, _>(connect).await?;
+        let mut conn = Connection::new(stream);
+        conn.get_mut().set_timeouts(timeouts.read, deadline);
+        let head = send_and_read_head(&mut conn, &wire).await?;
+        Ok((conn, head, key, permit))
+    }
+
+    // Errors are `HttpError`s wrapped in `io::Error`, as `Limit` needs.
+    // `timeout` bounds runtimes whose connects and handshakes block.
+    async fn connect(
+        &self,
+        key: &PoolKey,
+        timeout: Option<Duration>,
+    ) -> io::Result<ClientStream<R>> {
+        let resolver = self.resolver.as_deref();
+        let stream = connector::connect::<R>(&key.host, key.port, resolver, timeout)
+            .await
+            .map_err(|e| match HttpError::from(e) {
+                // An expired blocking connect, which `Limit` reports as a
+                // timeout
+                HttpError::Io(e) if e.kind() == io::ErrorKind::TimedOut => HttpError::Io(e),
+                HttpError::Io(e) => HttpError::Connect(e),
+                e => e,
+            })?;
+        match key.scheme.as_str() {
+            #[cfg(feature = "tls")]
+            "https" => {
+                let mut stream = stream;
+                stream.set_timeout(timeout)?;
+                let stream =
+                    TlsStream::connect(stream, connector::unbracket(&key.host), self.tls.clone())
+                        .await?;
+                Ok(ClientStream::tls(stream))
+            }
+            _ => Ok(ClientStream::plain(stream)),
+        }
+    }
+}
+
+// A request whose final response head has been read
+struct Exchange<R: Runtime> {
+    conn: Connection<ClientStream<R>>,
+    head: Head,
+    key: PoolKey,
+    permit: SemaphoreGuardArc,
+    // The method of the last request sent, which decides the body framing
+    method: Method,
+    redirects: Vec<Url>,
+}
+
+type OpenExchange<R> = (
+    Connection<ClientStream<R>>,
+    Head,
+    PoolKey,
+    SemaphoreGuardArc,
+);
+
+async fn send_and_read_head<S: Transport>(
+    conn: &mut Connection<S>,
+    wire: &[u8],
+) -> io::Result<Head> {
+    conn.send_request(wire).await?;
+    conn.read_head().await
+}
+
+fn pool_key(url: &Url) -> Result<PoolKey, HttpError> {
+    match url.scheme() {
+        "http" => {}
+        #[cfg(feature = "tls")]
+        "https" => {}
+        #[cfg(not(feature = "tls"))]
+        "https" => {
+            return Err(HttpError::UnsupportedUrl(
+                "https URLs need the `tls` feature".to_string(),
+            ))
+        }
+        scheme => {
+            return Err(HttpError::UnsupportedUrl(format!(
+                "unknown scheme {}",
+                scheme
+            )))
+        }
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| HttpError::UnsupportedUrl("missing host".to_string()))?;
+    let key = PoolKey {
+        scheme: url.scheme().to_string(),
+        host: host.to_string(),
+        // 80 for http, 443 for https
+        port: url.port_or_known_default().unwrap_or(80),
+    };
+    Ok(key)
+}
+
+fn is_stale_connection(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+    )
+}
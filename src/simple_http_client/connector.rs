@@ -0,0 +1,99 @@
+use super::resolver::Resolver;
+use crate::runtime::{timeout, Runtime};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+// How long a connection attempt runs alone before the next address is tried
+// in parallel (RFC 8305 recommends 250ms)
+const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+// Connects to `host`, which is a DNS name or an IP literal as returned by
+// `Url::host_str`, i.e. with IPv6 addresses in brackets. Names are looked up
+// with `resolver`, or the runtime's system resolver if there is none.
+// `connect_timeout` is the time left for connecting, see `connect_any`.
+pub(crate) async fn connect<R: Runtime>(
+    host: &str,
+    port: u16,
+    resolver: Option<&dyn Resolver>,
+    connect_timeout: Option<Duration>,
+) -> io::Result<R::Stream> {
+    let addrs = match unbracket(host).parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => match resolver {
+            Some(resolver) => resolver.resolve(host, port).await?,
+            None => R::resolve(host, port).await?,
+        },
+    };
+    let addrs = interleave(addrs);
+    connect_any::<R>(&addrs, ATTEMPT_DELAY, connect_timeout).await
+}
+
+// Strips the brackets around an IPv6 literal, e.g. for TLS server names
+pub(crate) fn unbracket(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+// Happy Eyeballs (RFC 8305): tries `addrs` in order, starting the next
+// attempt whenever the running ones fail or `attempt_delay` passes without
+// a connection. The first attempt to succeed wins and the others are
+// dropped. On a runtime whose connects block, the attempts run one after
+// another: each but the last gives up after `attempt_delay`, and the last
+// after whatever is left of `connect_timeout`.
+pub(crate) async fn connect_any<R: Runtime>(
+    addrs: &[SocketAddr],
+    attempt_delay: Duration,
+    connect_timeout: Option<Duration>,
+) -> io::Result<R::Stream> {
+    let deadline = connect_timeout.map(|limit| Instant::now() + limit);
+    let mut pending = addrs.iter().copied();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_error = None;
+    loop {
+        if let Some(addr) = pending.next() {
+            let left = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let limit = match pending.len() {
+                0 => left,
+                _ => Some(left.map_or(attempt_delay, |left| left.min(attempt_delay))),
+            };
+            attempts.push(R::connect(addr, limit));
+        }
+        if attempts.is_empty() {
+            return Err(last_error.unwrap_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "host has no addresses")
+            }));
+        }
+        let finished = if pending.len() > 0 {
+            timeout::<R, _>(attempt_delay, attempts.next()).await
+        } else {
+            Some(attempts.next().await)
+        };
+        match finished {
+            Some(Some(Ok(stream))) => return Ok(stream),
+            Some(Some(Err(e))) => last_error = Some(e),
+            // The delay passed: start the next attempt alongside
+            _ => {}
+        }
+    }
+}
+
+// Orders resolved addresses so that the families alternate, starting with
+// the family the resolver preferred. A broken IPv6 (or IPv4) path then only
+// costs one attempt delay.
+pub(crate) fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let prefer_v6 = addrs.first().is_some_and(SocketAddr::is_ipv6);
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == prefer_v6);
+    let mut ordered = Vec::with_capacity(preferred.len() + other.len());
+    let (mut preferred, mut other) = (preferred.into_iter(), other.into_iter());
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return ordered,
+            (first, second) => ordered.extend(first.into_iter().chain(second)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+// Everything that can make a request fail. Lower layers that have to speak
+// `io::Error` (e.g. `Transport` implementations) wrap an `HttpError` in it;
+// converting back with `HttpError::from` recovers the original variant.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HttpError {
+    // The URL could not be parsed
+    InvalidUrl(url::ParseError),
+    // The URL parsed, but cannot be requested, e.g. an unknown scheme
+    UnsupportedUrl(String),
+    // A request header has a name that is not a token or a value with CR,
+    // LF or NUL in it; holds the name
+    InvalidHeader(String),
+    // Establishing the TCP connection failed
+    Connect(io::Error),
+    Timeout { kind: TimeoutKind, after: Duration },
+    // The TLS handshake failed or the TLS session broke
+    Tls(Box<dyn Error + Send + Sync>),
+    // Sending the request or receiving the response failed
+    Io(io::Error),
+    // The server's response violates HTTP/1.1
+    MalformedResponse(String),
+    // Returned by `error_for_status` for 4xx and 5xx responses
+    Status(u16),
+    // The body exceeds `ClientConfig::max_body_size`
+    BodyTooLarge { limit: usize },
+    TooManyRedirects { limit: usize },
+    // The body is not valid text in its declared charset
+    Decode(String),
+}
+
+// Which limit of `Timeouts` expired
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutKind {
+    Connect,
+    Read,
+    Write,
+    // The request deadline set by `Timeouts::total`
+    Total,
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::InvalidUrl(e) => write!(f, "Invalid URL: {}", e),
+            HttpError::UnsupportedUrl(reason) => write!(f, "Unsupported URL: {}", reason),
+            HttpError::InvalidHeader(name) => write!(f, "Invalid header {:?}", name),
+            HttpError::Connect(e) => write!(f, "Connection failed: {}", e),
+            HttpError::Timeout { kind, after } => {
+                let what = match kind {
+                    TimeoutKind::Connect => "connect",
+                    TimeoutKind::Read => "read",
+                    TimeoutKind::Write => "write",
+                    TimeoutKind::Total => "request deadline",
+                };
+                write!(f, "{} timed out after {:?}", what, after)
+            }
+            HttpError::Tls(e) => write!(f, "TLS error: {}", e),
+            HttpError::Io(e) => write!(f, "I/O error: {}", e),
+            HttpError::MalformedResponse(msg) => write!(f, "Malformed HTTP response: {}", msg),
+            HttpError::Status(status) => write!(f, "HTTP status {}", status),
+            HttpError::BodyTooLarge { limit } => {
+                write!(f, "Response body larger than {} bytes", limit)
+            }
+            HttpError::TooManyRedirects { limit } => {
+                write!(f, "Too many redirects (limit is {})", limit)
+            }
+            HttpError::Decode(msg) => write!(f, "Cannot decode body: {}", msg),
+        }
+    }
+}
+
+impl Error for HttpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HttpError::InvalidUrl(e) => Some(e),
+            HttpError::Connect(e) | HttpError::Io(e) => Some(e),
+            HttpError::Tls(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HttpError {
+    fn from(e: io::Error) -> Self {
+        if e.get_ref().is_some_and(|inner| inner.is::<HttpError>()) {
+            let inner = e.into_inner().expect("checked above");
+            return *inner.downcast::<HttpError>().expect("checked above");
+        }
+        HttpError::Io(e)
+    }
+}
+
+impl From<HttpError> for io::Error {
+    fn from(e: HttpError) -> Self {
+        if let HttpError::Io(e) = e {
+            return e;
+        }
+        let kind = match &e {
+            HttpError::Connect(e) => e.kind(),
+            HttpError::Timeout { .. } => io::ErrorKind::TimedOut,
+            HttpError::InvalidUrl(_)
+            | HttpError::UnsupportedUrl(_)
+            | HttpError::InvalidHeader(_) => io::ErrorKind::InvalidInput,
+            HttpError::Tls(_)
+            | HttpError::MalformedResponse(_)
+            | HttpError::BodyTooLarge { .. }
+            | HttpError::Decode(_) => io::ErrorKind::InvalidData,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+impl From<url::ParseError> for HttpError {
+    fn from(e: url::ParseError) -> Self {
+        HttpError::InvalidUrl(e)
+    }
+}
@@ -0,0 +1,84 @@
+mod blocking;
+mod client;
+pub(crate) mod connector;
+mod error;
+mod pool;
+pub(crate) mod reader;
+mod redirect;
+pub(crate) mod request;
+mod resolver;
+pub(crate) mod response;
+mod retry;
+mod stream;
+mod streaming;
+pub(crate) mod target;
+#[cfg(feature = "tls")]
+mod tls;
+
+pub use blocking::BlockingClient;
+pub use client::{ClientConfig, HttpClient, Timeouts};
+pub use error::{HttpError, TimeoutKind};
+pub use pool::PoolConfig;
+pub use redirect::RedirectPolicy;
+pub use request::{Body, Method, RequestBuilder};
+pub use resolver::{CachingResolver, Resolver, StaticResolver, SystemResolver};
+pub use response::{HeaderMap, HttpResponse};
+pub use retry::RetryPolicy;
+pub use streaming::{BodyStream, StreamingResponse};
+pub use target::with_query;
+#[cfg(feature = "tls")]
+pub use tls::TlsConfig;
+
+use crate::runtime::{DefaultRuntime, Runtime};
+
+// A simple runtime-agnostic HTTP client for one-off requests. Each call uses
+// a fresh connection; use `HttpClient` to reuse connections across requests.
+pub struct SimpleHttpClient;
+
+impl SimpleHttpClient {
+    // Makes an HTTP GET request and returns the parsed response
+    pub async fn get(url: &str) -> Result<HttpResponse, HttpError> {
+        Self::get_with::<DefaultRuntime>(url).await
+    }
+
+    // Makes an HTTP GET request with the given query parameters appended to `url`
+    pub async fn get_with_query<I, K, V>(url: &str, params: I) -> Result<HttpResponse, HttpError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let url = with_query(url, params)?;
+        Self::get(url.as_str()).await
+    }
+
+    // Makes an HTTP POST request with a text body and returns the parsed response
+    pub async fn post(url: &str, body: &str) -> Result<HttpResponse, HttpError> {
+        Self::post_with::<DefaultRuntime>(url, body).await
+    }
+
+    // Starts building a request with any method, headers and body on the
+    // default runtime, e.g.
+    // `SimpleHttpClient::request(Method::Put, url).body(bytes).send().await`
+    pub fn request(method: Method, url: &str) -> RequestBuilder<DefaultRuntime> {
+        HttpClient::new().request(method, url)
+    }
+
+    // A client whose calls block the current thread instead of returning
+    // futures, e.g. `SimpleHttpClient::blocking().get(url)?`. Works in every
+    // build, with or without an async runtime feature.
+    pub fn blocking() -> BlockingClient {
+        BlockingClient::new()
+    }
+
+    // Like `get`, but on an explicitly chosen runtime,
+    // e.g. `SimpleHttpClient::get_with::<SmolRuntime>(url)`
+    pub async fn get_with<R: Runtime>(url: &str) -> Result<HttpResponse, HttpError> {
+        HttpClient::<R>::default().get(url).await
+    }
+
+    // Like `post`, but on an explicitly chosen runtime
+    pub async fn post_with<R: Runtime>(url: &str, body: &str) -> Result<HttpResponse, HttpError> {
+        HttpClient::<R>::default().post(url, body).await
+    }
+}
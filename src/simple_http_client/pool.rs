@@ -0,0 +1,113 @@
+use super::reader::Connection;
+use crate::runtime::Transport;
+use async_lock::{Semaphore, SemaphoreGuardArc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Limits for the keep-alive connection pool of an `HttpClient`
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    // Idle connections kept per host; 0 disables connection reuse
+    pub max_idle_per_host: usize,
+    // Idle connections older than this are closed instead of reused
+    pub idle_timeout: Duration,
+    // Requests in flight per host; further requests wait for a free slot
+    pub max_per_host: usize,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: 8,
+            idle_timeout: Duration::from_secs(90),
+            max_per_host: 32,
+        }
+    }
+}
+
+// Connections are only shared between requests to the same origin
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PoolKey {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+}
+
+struct Idle<S> {
+    conn: Connection<S>,
+    since: Instant,
+}
+
+struct HostEntry<S> {
+    idle: VecDeque<Idle<S>>,
+    permits: Arc<Semaphore>,
+}
+
+// Per-host pool of idle keep-alive connections. Expired connections are
+// dropped lazily whenever the pool for their host is touched.
+pub(crate) struct Pool<S> {
+    config: PoolConfig,
+    hosts: Mutex<HashMap<PoolKey, HostEntry<S>>>,
+}
+
+impl<S: Transport> Pool<S> {
+    pub fn new(config: PoolConfig) -> Self {
+        Self {
+            config,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Waits until a request to `key` may start and returns the permit that
+    // holds its slot, together with a fresh idle connection if there is one
+    pub async fn checkout(&self, key: &PoolKey) -> (SemaphoreGuardArc, Option<Connection<S>>) {
+        let permits = {
+            let mut hosts = self.hosts.lock().unwrap();
+            let entry = hosts.entry(key.clone()).or_insert_with(|| HostEntry {
+                idle: VecDeque::new(),
+                permits: Arc::new(Semaphore::new(self.config.max_per_host.max(1))),
+            });
+            entry.permits.clone()
+        };
+        let permit = permits.acquire_arc().await;
+
+        let mut hosts = self.hosts.lock().unwrap();
+        let conn = hosts.get_mut(key).and_then(|entry| {
+            self.evict_expired(entry);
+            entry.idle.pop_back().map(|idle| idle.conn)
+        });
+        (permit, conn)
+    }
+
+    // Returns a connection whose last response was fully read
+    pub fn checkin(&self, key: &PoolKey, conn: Connection<S>) {
+        if self.config.max_idle_per_host == 0 || !conn.is_reusable() {
+            return;
+        }
+        let mut hosts = self.hosts.lock().unwrap();
+        if let Some(entry) = hosts.get_mut(key) {
+            self.evict_expired(entry);
+            if entry.idle.len() == self.config.max_idle_per_host {
+                entry.idle.pop_front();
+            }
+            entry.idle.push_back(Idle {
+                conn,
+                since: Instant::now(),
+            });
+        }
+    }
+
+    pub fn idle_count(&self, key: &PoolKey) -> usize {
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts.get_mut(key).map_or(0, |entry| {
+            self.evict_expired(entry);
+            entry.idle.len()
+        })
+    }
+
+    fn evict_expired(&self, entry: &mut HostEntry<S>) {
+        let timeout = self.config.idle_timeout;
+        entry.idle.retain(|idle| idle.since.elapsed() < timeout);
+    }
+}
@@ -0,0 +1,417 @@
+use super::error::HttpError;
+use super::request::Method;
+use super::response::{malformed, HeaderMap, HttpResponse};
+use crate::runtime::Transport;
+use bytes::{Buf, Bytes, BytesMut};
+use std::io;
+
+// Upper bounds that keep a misbehaving server from making us buffer forever
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+const MAX_LINE_SIZE: usize = 8 * 1024;
+
+// Status line and headers of a response
+#[derive(Debug)]
+pub(crate) struct Head {
+    pub version: String,
+    pub status: u16,
+    pub reason: String,
+    pub headers: HeaderMap,
+}
+
+impl Head {
+    // Parses a response head, without the terminating empty line
+    pub fn parse(head: &[u8]) -> io::Result<Self> {
+        let head = std::str::from_utf8(head).map_err(|_| malformed("head is not UTF-8"))?;
+        let mut lines = head.split("\r\n");
+
+        let status_line = lines.next().ok_or_else(|| malformed("empty response"))?;
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts
+            .next()
+            .filter(|v| v.starts_with("HTTP/"))
+            .ok_or_else(|| malformed("status line does not start with HTTP/"))?;
+        let status = parts
+            .next()
+            .and_then(|code| code.parse::<u16>().ok())
+            .filter(|code| (100..1000).contains(code))
+            .ok_or_else(|| malformed("invalid status code"))?;
+        // The reason phrase is optional: "HTTP/1.1 200" is a valid status line
+        let reason = parts.next().unwrap_or("");
+
+        let mut headers = HeaderMap::new();
+        for line in lines {
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| malformed("header line without ':'"))?;
+            headers.append(name.trim(), value.trim());
+        }
+
+        Ok(Self {
+            version: version.to_string(),
+            status,
+            reason: reason.to_string(),
+            headers,
+        })
+    }
+
+    // Whether the server is willing to keep the connection open: the default
+    // for HTTP/1.1, opt-in for HTTP/1.0
+    pub fn keep_alive(&self) -> bool {
+        let has_token = |token: &str| {
+            self.headers
+                .get_all("Connection")
+                .flat_map(|value| value.split(','))
+                .any(|value| value.trim().eq_ignore_ascii_case(token))
+        };
+        if has_token("close") {
+            return false;
+        }
+        self.version != "HTTP/1.0" || has_token("keep-alive")
+    }
+
+    // Works out how the body is delimited, following RFC 9112 section 6.3.
+    // Responses to HEAD never have a body, whatever their headers announce.
+    pub fn body_kind(&self, method: Method) -> io::Result<BodyKind> {
+        if method == Method::Head
+            || (100..200).contains(&self.status)
+            || self.status == 204
+            || self.status == 304
+        {
+            return Ok(BodyKind::Empty);
+        }
+
+        if let Some(encodings) = self.headers.get("Transfer-Encoding") {
+            // Only a final "chunked" coding tells us where the body ends
+            let last = encodings.rsplit(',').next().unwrap_or("").trim();
+            return Ok(if last.eq_ignore_ascii_case("chunked") {
+                BodyKind::Chunked
+            } else {
+                BodyKind::UntilClose
+            });
+        }
+
+        let mut length = None;
+        for value in self.headers.get_all("Content-Length") {
+            let value: usize = value
+                .trim()
+                .parse()
+                .map_err(|_| malformed("invalid Content-Length"))?;
+            if length.is_some_and(|length| length != value) {
+                return Err(malformed("conflicting Content-Length headers"));
+            }
+            length = Some(value);
+        }
+
+        Ok(match length {
+            Some(length) => BodyKind::Length(length),
+            None => BodyKind::UntilClose,
+        })
+    }
+}
+
+// How the end of a response body is found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BodyKind {
+    Empty,
+    Length(usize),
+    Chunked,
+    // HTTP/1.0 style: the body ends when the server closes the connection
+    UntilClose,
+}
+
+// Incremental decoder for `Transfer-Encoding: chunked` bodies. It consumes
+// whatever is available in the input buffer and can be fed again once more
+// data has been read from the socket.
+#[derive(Debug, Default)]
+pub(crate) struct ChunkedDecoder {
+    state: ChunkedState,
+    trailers: HeaderMap,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+enum ChunkedState {
+    #[default]
+    Size,
+    Data(usize),
+    DataEnd,
+    Trailers,
+    Done,
+}
+
+impl ChunkedDecoder {
+    // Moves decoded body bytes from `input` to `output`. Returns true once the
+    // terminating chunk and trailers have been consumed.
+    pub fn decode(&mut self, input: &mut BytesMut, output: &mut BytesMut) -> io::Result<bool> {
+        loop {
+            match self.state {
+                ChunkedState::Size => {
+                    let Some(line) = take_line(input)? else {
+                        return Ok(false);
+                    };
+                    // Chunk extensions after ';' carry nothing we need
+                    let size = line.split(';').next().unwrap_or("").trim();
+                    let size = usize::from_str_radix(size, 16)
+                        .map_err(|_| malformed("invalid chunk size"))?;
+                    self.state = if size == 0 {
+                        ChunkedState::Trailers
+                    } else {
+                        ChunkedState::Data(size)
+                    };
+                }
+                ChunkedState::Data(remaining) => {
+                    if input.is_empty() {
+                        return Ok(false);
+                    }
+                    let n = remaining.min(input.len());
+                    output.extend_from_slice(&input.split_to(n));
+                    self.state = if n == remaining {
+                        ChunkedState::DataEnd
+                    } else {
+                        ChunkedState::Data(remaining - n)
+                    };
+                }
+                ChunkedState::DataEnd => {
+                    let Some(line) = take_line(input)? else {
+                        return Ok(false);
+                    };
+                    if !line.is_empty() {
+                        return Err(malformed("chunk data not followed by CRLF"));
+                    }
+                    self.state = ChunkedState::Size;
+                }
+                ChunkedState::Trailers => {
+                    let Some(line) = take_line(input)? else {
+                        return Ok(false);
+                    };
+                    if line.is_empty() {
+                        self.state = ChunkedState::Done;
+                    } else {
+                        let (name, value) = line
+                            .split_once(':')
+                            .ok_or_else(|| malformed("trailer line without ':'"))?;
+                        self.trailers.append(name.trim(), value.trim());
+                    }
+                }
+                ChunkedState::Done => return Ok(true),
+            }
+        }
+    }
+
+    pub fn into_trailers(self) -> HeaderMap {
+        self.trailers
+    }
+}
+
+// Removes one CRLF-terminated line from the front of `input`
+fn take_line(input: &mut BytesMut) -> io::Result<Option<String>> {
+    match input.windows(2).position(|w| w == b"\r\n") {
+        Some(end) => {
+            let line = input.split_to(end);
+            input.advance(2);
+            String::from_utf8(line.to_vec())
+                .map(Some)
+                .map_err(|_| malformed("line is not UTF-8"))
+        }
+        None if input.len() > MAX_LINE_SIZE => Err(malformed("line too long")),
+        None => Ok(None),
+    }
+}
+
+// Position just past the empty line that ends a response head
+pub(crate) fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+// A client connection that writes requests and reads framed HTTP responses
+// from a transport. Bytes received beyond the end of one response stay
+// buffered for the next.
+pub(crate) struct Connection<T> {
+    stream: T,
+    buf: BytesMut,
+    reusable: bool,
+    received: bool,
+}
+
+impl<T: Transport> Connection<T> {
+    pub fn new(stream: T) -> Self {
+        Self {
+            stream,
+            buf: BytesMut::with_capacity(8 * 1024),
+            reusable: false,
+            received: false,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.stream
+    }
+
+    pub async fn send_request(&mut self, request: &[u8]) -> io::Result<()> {
+        self.reusable = false;
+        self.received = false;
+        self.stream.write_all(request).await?;
+        self.stream.flush().await
+    }
+
+    // Whether the last response was read completely and the server agreed to
+    // keep the connection open, so another request may be sent on it
+    pub fn is_reusable(&self) -> bool {
+        self.reusable && self.buf.is_empty()
+    }
+
+    // Whether any part of a response arrived since the last request was sent
+    pub fn has_received_response(&self) -> bool {
+        self.received
+    }
+
+    // Reads more data from the socket into the buffer; returns 0 on EOF
+    async fn fill(&mut self) -> io::Result<usize> {
+        let mut chunk = [0u8; 8 * 1024];
+        let n = self.stream.read(&mut chunk).await?;
+        self.buf.extend_from_slice(&chunk[..n]);
+        self.received |= n > 0;
+        Ok(n)
+    }
+
+    async fn fill_or_eof_error(&mut self, what: &str) -> io::Result<()> {
+        if self.fill().await? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("connection closed before end of {}", what),
+            ));
+        }
+        Ok(())
+    }
+
+    // Reads the next final (non-1xx) response head
+    pub async fn read_head(&mut self) -> io::Result<Head> {
+        loop {
+            let head = loop {
+                if let Some(end) = find_head_end(&self.buf) {
+                    let head = self.buf.split_to(end);
+                    break Head::parse(&head[..end - 4])?;
+                }
+                if self.buf.len() > MAX_HEAD_SIZE {
+                    return Err(malformed("response head too large"));
+                }
+                self.fill_or_eof_error("response head").await?;
+            };
+            // Interim responses such as "100 Continue" precede the real one
+            if (100..200).contains(&head.status) && head.status != 101 {
+                continue;
+            }
+            return Ok(head);
+        }
+    }
+
+    // Returns the next piece of the body described by `body`, or `None` once
+    // the body is complete. At most one socket read is buffered at a time,
+    // so bodies can be streamed with bounded memory.
+    pub async fn read_chunk(&mut self, body: &mut BodyReader) -> io::Result<Option<Bytes>> {
+        loop {
+            let chunk = match &mut body.state {
+                BodyState::Done => return Ok(None),
+                BodyState::Length(0) => None,
+                BodyState::Length(remaining) => {
+                    if self.buf.is_empty() {
+                        self.fill_or_eof_error("response body").await?;
+                    }
+                    let n = (*remaining).min(self.buf.len());
+                    *remaining -= n;
+                    Some(self.buf.split_to(n).freeze())
+                }
+                BodyState::Chunked(decoder) => {
+                    let mut out = BytesMut::new();
+                    let done = decoder.decode(&mut self.buf, &mut out)?;
+                    if !out.is_empty() {
+                        return Ok(Some(out.freeze()));
+                    }
+                    if !done {
+                        self.fill_or_eof_error("chunked body").await?;
+                        continue;
+                    }
+                    body.trailers = std::mem::take(&mut decoder.trailers);
+                    None
+                }
+                BodyState::UntilClose => {
+                    if self.buf.is_empty() && self.fill().await? == 0 {
+                        None
+                    } else {
+                        Some(self.buf.split().freeze())
+                    }
+                }
+            };
+            if chunk.is_none() {
+                let until_close = matches!(body.state, BodyState::UntilClose);
+                self.reusable = body.keep_alive && !until_close;
+                body.state = BodyState::Done;
+            }
+            return Ok(chunk);
+        }
+    }
+
+    // Reads the whole body that follows `head` into memory, failing with
+    // `HttpError::BodyTooLarge` once it exceeds `limit`
+    pub async fn read_to_end(
+        &mut self,
+        head: Head,
+        method: Method,
+        limit: Option<usize>,
+    ) -> io::Result<HttpResponse> {
+        let mut reader = BodyReader::new(&head, method)?;
+        let too_large = |size: usize| match limit {
+            Some(limit) if size > limit => Err(HttpError::BodyTooLarge { limit }),
+            _ => Ok(()),
+        };
+        if let BodyKind::Length(length) = head.body_kind(method)? {
+            too_large(length)?;
+        }
+        let mut body = BytesMut::new();
+        while let Some(chunk) = self.read_chunk(&mut reader).await? {
+            body.extend_from_slice(&chunk);
+            too_large(body.len())?;
+        }
+        Ok(HttpResponse::from_parts(
+            head,
+            body.freeze(),
+            reader.trailers,
+        ))
+    }
+}
+
+// Progress through one response body, advanced by `Connection::read_chunk`
+pub(crate) struct BodyReader {
+    state: BodyState,
+    keep_alive: bool,
+    trailers: HeaderMap,
+}
+
+enum BodyState {
+    Length(usize),
+    Chunked(ChunkedDecoder),
+    UntilClose,
+    Done,
+}
+
+impl BodyReader {
+    pub fn new(head: &Head, method: Method) -> io::Result<Self> {
+        let state = match head.body_kind(method)? {
+            BodyKind::Empty => BodyState::Length(0),
+            BodyKind::Length(length) => BodyState::Length(length),
+            BodyKind::Chunked => BodyState::Chunked(ChunkedDecoder::default()),
+            BodyKind::UntilClose => BodyState::UntilClose,
+        };
+        Ok(Self {
+            state,
+            keep_alive: head.keep_alive(),
+            trailers: HeaderMap::new(),
+        })
+    }
+
+    // Trailer fields of a chunked body, available once it has been read
+    pub fn trailers(&self) -> &HeaderMap {
+        &self.trailers
+    }
+}
@@ -0,0 +1,74 @@
+use super::error::HttpError;
+use super::reader::Head;
+use super::request::{Method, Request};
+
+// How an `HttpClient` treats 3xx responses that carry a Location header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedirectPolicy {
+    max_redirects: usize,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::limited(10)
+    }
+}
+
+impl RedirectPolicy {
+    // Follows at most `max_redirects` redirects per request; one more fails
+    // the request
+    pub fn limited(max_redirects: usize) -> Self {
+        Self { max_redirects }
+    }
+
+    // Returns redirect responses to the caller as they are
+    pub fn none() -> Self {
+        Self::limited(0)
+    }
+
+    pub fn max_redirects(&self) -> usize {
+        self.max_redirects
+    }
+
+    pub(crate) fn follows(&self) -> bool {
+        self.max_redirects > 0
+    }
+}
+
+// Headers that carry credentials and must not leak to another origin
+const SENSITIVE_HEADERS: [&str; 3] = ["Authorization", "Cookie", "Proxy-Authorization"];
+
+// The request to send after `head` answered `request`, or `None` when the
+// response is not a redirect that can be followed
+pub(crate) fn next_request(request: &Request, head: &Head) -> Result<Option<Request>, HttpError> {
+    if !matches!(head.status, 301 | 302 | 303 | 307 | 308) {
+        return Ok(None);
+    }
+    let Some(location) = head.headers.get("Location") else {
+        return Ok(None);
+    };
+    let url = request.url.join(location.trim()).map_err(|e| {
+        HttpError::MalformedResponse(format!("invalid Location {:?}: {}", location, e))
+    })?;
+
+    let mut next = request.clone();
+    next.url = url;
+    // 307 and 308 repeat the request unchanged. 303 always switches to GET;
+    // 301 and 302 do so for POST, as browsers have always done.
+    let to_get = match head.status {
+        303 => request.method != Method::Head,
+        301 | 302 => request.method == Method::Post,
+        _ => false,
+    };
+    if to_get {
+        next.method = Method::Get;
+        next.body.clear();
+        next.headers.remove("Content-Type");
+    }
+    if next.url.origin() != request.url.origin() {
+        for name in SENSITIVE_HEADERS {
+            next.headers.remove(name);
+        }
+    }
+    Ok(Some(next))
+}
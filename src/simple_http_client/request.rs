@@ -0,0 +1,257 @@
+use super::client::{HttpClient, Timeouts};
+use super::error::HttpError;
+use super::redirect::RedirectPolicy;
+use super::response::{is_valid_header, HeaderMap, HttpResponse};
+use super::retry::RetryPolicy;
+use super::streaming::StreamingResponse;
+use super::target::{host_header, parse_url, request_target};
+use crate::runtime::Runtime;
+use bytes::Bytes;
+use std::fmt;
+use std::time::Duration;
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Options => "OPTIONS",
+        }
+    }
+
+    // Methods whose semantics say repeating the request has the same effect
+    // as sending it once (RFC 9110 section 9.2.2)
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Method::Post | Method::Patch)
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// A request body. Converts from the usual owned and borrowed byte and string
+// types, so `RequestBuilder::body` accepts any of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Body(Bytes);
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Body(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body(bytes.into())
+    }
+}
+
+impl From<&[u8]> for Body {
+    fn from(bytes: &[u8]) -> Self {
+        Body(Bytes::copy_from_slice(bytes))
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        Body(text.into())
+    }
+}
+
+impl From<&str> for Body {
+    fn from(text: &str) -> Self {
+        Body(Bytes::copy_from_slice(text.as_bytes()))
+    }
+}
+
+// A fully specified request, ready to be written to a connection
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    // Overrides of the client's timeouts; unset fields use the client's
+    pub timeouts: Timeouts,
+    // Overrides the client's redirect policy
+    pub redirect: Option<RedirectPolicy>,
+    // Overrides the client's retry policy
+    pub retry: Option<RetryPolicy>,
+}
+
+impl Request {
+    pub fn new(method: Method, url: Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            timeouts: Timeouts {
+                connect: None,
+                read: None,
+                total: None,
+            },
+            redirect: None,
+            retry: None,
+        }
+    }
+
+    // Serializes the request head and body in HTTP/1.1 wire format. Host and
+    // the body framing are always derived from the URL and body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\n",
+            self.method,
+            request_target(&self.url),
+            host_header(&self.url)
+        );
+        for (name, value) in self.headers.iter() {
+            let derived = ["Host", "Content-Length", "Transfer-Encoding"];
+            if derived.iter().any(|d| name.eq_ignore_ascii_case(d)) {
+                continue;
+            }
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        // Methods that usually carry a body announce even an empty one
+        let expects_body = matches!(self.method, Method::Post | Method::Put | Method::Patch);
+        if expects_body || !self.body.is_empty() {
+            head.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+        head.push_str("\r\n");
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+}
+
+// Builds a request step by step and sends it with `send`. Obtained from
+// `HttpClient::request` or `SimpleHttpClient::request`. An invalid URL or
+// header is reported when the request is sent.
+pub struct RequestBuilder<R: Runtime> {
+    client: HttpClient<R>,
+    request: Result<Request, HttpError>,
+}
+
+impl<R: Runtime> RequestBuilder<R> {
+    pub(crate) fn new(client: HttpClient<R>, method: Method, url: &str) -> Self {
+        Self {
+            client,
+            request: parse_url(url).map(|url| Request::new(method, url)),
+        }
+    }
+
+    // Adds a header; repeated calls with the same name add more values. A
+    // name that is not a token or a value with CR, LF or NUL in it makes
+    // `send` fail with `HttpError::InvalidHeader`.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let (name, value) = (name.into(), value.into());
+        self.request = self.request.and_then(|mut request| {
+            check_header(&name, &value)?;
+            request.headers.append(name, value);
+            Ok(request)
+        });
+        self
+    }
+
+    // Appends a form-urlencoded query parameter to the URL
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.url.query_pairs_mut().append_pair(key, value);
+        }
+        self
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        let content_type = content_type.into();
+        self.request = self.request.and_then(|mut request| {
+            check_header("Content-Type", &content_type)?;
+            request.headers.insert("Content-Type", content_type);
+            Ok(request)
+        });
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.body = body.into().0;
+        }
+        self
+    }
+
+    // Limits the whole request, including reading the body
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.timeouts.total = Some(timeout);
+        }
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.timeouts.connect = Some(timeout);
+        }
+        self
+    }
+
+    // Limits how long the server may stay silent during the request
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.timeouts.read = Some(timeout);
+        }
+        self
+    }
+
+    // Overrides the client's redirect policy for this request
+    pub fn redirect(mut self, policy: RedirectPolicy) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.redirect = Some(policy);
+        }
+        self
+    }
+
+    // Overrides the client's retry policy for this request
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        if let Ok(request) = &mut self.request {
+            request.retry = Some(policy);
+        }
+        self
+    }
+
+    // Sends the request and reads the whole response into memory
+    pub async fn send(self) -> Result<HttpResponse, HttpError> {
+        self.client.execute(self.request?).await
+    }
+
+    // Sends the request and returns as soon as the response head has been
+    // read; the body is then consumed as a stream of chunks
+    pub async fn send_streaming(self) -> Result<StreamingResponse, HttpError> {
+        self.client.execute_streaming(self.request?).await
+    }
+}
+
+fn check_header(name: &str, value: &str) -> Result<(), HttpError> {
+    match is_valid_header(name, value) {
+        true => Ok(()),
+        false => Err(HttpError::InvalidHeader(name.to_string())),
+    }
+}
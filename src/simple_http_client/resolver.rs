@@ -0,0 +1,162 @@
+use crate::runtime::Runtime;
+use futures_util::future::BoxFuture;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Turns a host name into the addresses an `HttpClient` connects to. Set one
+// with `ClientConfig::resolver`; without one the runtime's system resolver
+// is used. IP literals in URLs never reach the resolver.
+pub trait Resolver: fmt::Debug + Send + Sync + 'static {
+    // Every address of `host`, with `port` filled in
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>>;
+}
+
+// The operating system's resolver, as provided by runtime `R`. Useful as the
+// fallback or inner resolver of the others.
+pub struct SystemResolver<R: Runtime> {
+    _runtime: PhantomData<fn() -> R>,
+}
+
+impl<R: Runtime> SystemResolver<R> {
+    pub fn new() -> Self {
+        Self {
+            _runtime: PhantomData,
+        }
+    }
+}
+
+impl<R: Runtime> Default for SystemResolver<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Runtime> fmt::Debug for SystemResolver<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SystemResolver")
+    }
+}
+
+impl<R: Runtime> Resolver for SystemResolver<R> {
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+        Box::pin(R::resolve(host, port))
+    }
+}
+
+// Answers from an in-memory map of host names to addresses, e.g. to point
+// `httpbin.org` at a local test server. Other hosts go to the fallback
+// resolver, or fail with `io::ErrorKind::NotFound` if there is none.
+#[derive(Debug, Default)]
+pub struct StaticResolver {
+    hosts: HashMap<String, Vec<IpAddr>>,
+    fallback: Option<Arc<dyn Resolver>>,
+}
+
+impl StaticResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Resolves `host` (case-insensitively) to `addrs`, replacing any earlier
+    // entry for it
+    pub fn with_host(mut self, host: &str, addrs: impl IntoIterator<Item = IpAddr>) -> Self {
+        self.hosts
+            .insert(host.to_ascii_lowercase(), addrs.into_iter().collect());
+        self
+    }
+
+    pub fn with_fallback(mut self, fallback: impl Resolver) -> Self {
+        self.fallback = Some(Arc::new(fallback));
+        self
+    }
+}
+
+impl Resolver for StaticResolver {
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+        Box::pin(async move {
+            if let Some(ips) = self.hosts.get(&host.to_ascii_lowercase()) {
+                return Ok(ips.iter().map(|ip| SocketAddr::new(*ip, port)).collect());
+            }
+            match &self.fallback {
+                Some(fallback) => fallback.resolve(host, port).await,
+                None => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no static address for {}", host),
+                )),
+            }
+        })
+    }
+}
+
+// Remembers the answers of another resolver for `ttl`. Failed lookups are
+// not cached.
+#[derive(Debug)]
+pub struct CachingResolver<T> {
+    inner: T,
+    ttl: Duration,
+    cache: Mutex<HashMap<(String, u16), Cached>>,
+}
+
+#[derive(Debug)]
+struct Cached {
+    addrs: Vec<SocketAddr>,
+    expires: Instant,
+}
+
+impl<T: Resolver> CachingResolver<T> {
+    pub fn new(inner: T, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Resolver> Resolver for CachingResolver<T> {
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+        Box::pin(async move {
+            let key = (host.to_ascii_lowercase(), port);
+            {
+                let mut cache = self.cache.lock().unwrap();
+                let now = Instant::now();
+                cache.retain(|_, cached| cached.expires > now);
+                if let Some(cached) = cache.get(&key) {
+                    return Ok(cached.addrs.clone());
+                }
+            }
+            let addrs = self.inner.resolve(host, port).await?;
+            let cached = Cached {
+                addrs: addrs.clone(),
+                expires: Instant::now() + self.ttl,
+            };
+            self.cache.lock().unwrap().insert(key, cached);
+            Ok(addrs)
+        })
+    }
+}
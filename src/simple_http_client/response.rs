@@ -0,0 +1,206 @@
+use super::error::HttpError;
+use super::reader::{find_head_end, BodyKind, ChunkedDecoder, Head};
+use super::request::Method;
+use bytes::{Bytes, BytesMut};
+use encoding_rs::Encoding;
+use std::io;
+use url::Url;
+
+// An ordered list of HTTP headers with case-insensitive name lookup.
+// Names keep the casing they were received with, so the map can be written
+// back to the wire unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderMap {
+    entries: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the first value for `name`, ignoring ASCII case
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    // Returns every value for `name` in the order they were received
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    // Replaces all existing values for `name` with a single value
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        self.remove(&name);
+        self.entries.push((name, value.into()));
+    }
+
+    // Adds a value for `name` without touching existing ones
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.entries.push((name.into(), value.into()));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// Whether a header can be written to the wire as is: the name must be an
+// RFC 9110 token and the value must not contain CR, LF or NUL, which would
+// end the line early and smuggle in further headers
+pub(crate) fn is_valid_header(name: &str, value: &str) -> bool {
+    let is_tchar = |b: u8| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b);
+    !name.is_empty() && name.bytes().all(is_tchar) && !value.contains(['\r', '\n', '\0'])
+}
+
+// A parsed HTTP response: status line, headers and the decoded body.
+// `trailers` holds the trailer fields sent after a chunked body, if any.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub version: String,
+    pub status: u16,
+    pub reason: String,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    pub trailers: HeaderMap,
+    // URLs that answered with a redirect on the way to this response, in
+    // the order they were requested
+    pub redirects: Vec<Url>,
+}
+
+impl HttpResponse {
+    pub(crate) fn from_parts(head: Head, body: Bytes, trailers: HeaderMap) -> Self {
+        Self {
+            version: head.version,
+            status: head.status,
+            reason: head.reason,
+            headers: head.headers,
+            body,
+            trailers,
+            redirects: Vec::new(),
+        }
+    }
+
+    // Parses a complete HTTP/1.x response as read from the socket, honouring
+    // Content-Length and chunked transfer-encoding. Assumes the request was
+    // not a HEAD request.
+    pub fn parse(raw: &[u8]) -> Result<Self, HttpError> {
+        let head_end =
+            find_head_end(raw).ok_or_else(|| malformed("missing end of response head"))?;
+        let head = Head::parse(&raw[..head_end - 4])?;
+        let mut rest = BytesMut::from(&raw[head_end..]);
+
+        let (body, trailers) = match head.body_kind(Method::Get)? {
+            BodyKind::Empty => (Bytes::new(), HeaderMap::new()),
+            BodyKind::Length(length) if rest.len() >= length => {
+                (rest.split_to(length).freeze(), HeaderMap::new())
+            }
+            BodyKind::Length(_) => return Err(malformed("body shorter than Content-Length").into()),
+            BodyKind::Chunked => {
+                let mut decoder = ChunkedDecoder::default();
+                let mut body = BytesMut::new();
+                if !decoder.decode(&mut rest, &mut body)? {
+                    return Err(malformed("incomplete chunked body").into());
+                }
+                (body.freeze(), decoder.into_trailers())
+            }
+            BodyKind::UntilClose => (rest.freeze(), HeaderMap::new()),
+        };
+
+        Ok(Self::from_parts(head, body, trailers))
+    }
+
+    // 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    // 3xx
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.status)
+    }
+
+    // 4xx
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.status)
+    }
+
+    // 5xx
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.status)
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get("Content-Type")
+    }
+
+    // The `charset` parameter of the Content-Type header, if present
+    pub fn charset(&self) -> Option<&str> {
+        self.content_type()?
+            .split(';')
+            .skip(1)
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| value.trim().trim_matches('"'))
+    }
+
+    // The raw body, exactly as received. Cheap: `Bytes` is reference counted.
+    pub fn bytes(&self) -> Bytes {
+        self.body.clone()
+    }
+
+    // Decodes the body using the charset from Content-Type, defaulting to
+    // UTF-8. A byte order mark takes precedence over the header. Fails on
+    // an unknown charset or bytes that are invalid in that charset.
+    pub fn text(&self) -> Result<String, HttpError> {
+        let label = self.charset().unwrap_or("utf-8");
+        let encoding = Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| HttpError::Decode(format!("unknown charset: {}", label)))?;
+        let (text, used, had_errors) = encoding.decode(&self.body);
+        if had_errors {
+            return Err(HttpError::Decode(format!(
+                "body is not valid {}",
+                used.name()
+            )));
+        }
+        Ok(text.into_owned())
+    }
+
+    // Turns a 4xx or 5xx response into `HttpError::Status`
+    pub fn error_for_status(self) -> Result<Self, HttpError> {
+        if self.is_client_error() || self.is_server_error() {
+            return Err(HttpError::Status(self.status));
+        }
+        Ok(self)
+    }
+}
+
+pub(crate) fn malformed(msg: &str) -> io::Error {
+    HttpError::MalformedResponse(msg.to_string()).into()
+}
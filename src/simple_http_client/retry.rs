@@ -0,0 +1,81 @@
+use super::error::HttpError;
+use super::request::Method;
+use std::io;
+use std::time::Duration;
+
+// When and how often an `HttpClient` repeats a failed request. A request is
+// retried if connecting or reading the response head fails with an
+// `HttpError::Connect` or `HttpError::Io` of one of the `retry_on_errors`
+// kinds, or if the response status is in `retry_on_status`.
+// Errors while reading the body of a response are not retried, and neither
+// are `HttpError::Timeout`s unless `io::ErrorKind::TimedOut` is added to the
+// list.
+//
+// Between attempts the client sleeps on the runtime's timer for a random
+// delay between zero and `base_delay * 2^(attempt - 1)`, capped at
+// `max_delay` ("full jitter"), so clients failing together do not retry in
+// lockstep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    // Attempts in total, including the first; 1 disables retries
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub retry_on_status: Vec<u16>,
+    pub retry_on_errors: Vec<io::ErrorKind>,
+    // Also retry methods that are not idempotent, e.g. POST. Only safe when
+    // the server deduplicates requests.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            retry_on_status: vec![429, 502, 503, 504],
+            retry_on_errors: vec![
+                io::ErrorKind::ConnectionRefused,
+                io::ErrorKind::ConnectionReset,
+                io::ErrorKind::ConnectionAborted,
+                io::ErrorKind::BrokenPipe,
+                io::ErrorKind::UnexpectedEof,
+            ],
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Sends every request exactly once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn retries_error(&self, error: &HttpError) -> bool {
+        let kind = match error {
+            HttpError::Connect(e) | HttpError::Io(e) => e.kind(),
+            HttpError::Timeout { .. } => io::ErrorKind::TimedOut,
+            _ => return false,
+        };
+        self.retry_on_errors.contains(&kind)
+    }
+
+    pub(crate) fn allows(&self, method: Method) -> bool {
+        method.is_idempotent() || self.retry_non_idempotent
+    }
+
+    // The randomized delay before attempt number `attempt + 1`
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let ceiling = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        ceiling.mul_f64(fastrand::f64())
+    }
+}
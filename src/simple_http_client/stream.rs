@@ -0,0 +1,133 @@
+use super::error::{HttpError, TimeoutKind};
+#[cfg(feature = "tls")]
+use super::tls::TlsStream;
+use crate::runtime::{is_socket_timeout, timeout, Runtime, Transport};
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+// The connection an `HttpClient` talks through: the runtime's plain socket
+// for `http` URLs, or that socket wrapped in TLS for `https` URLs. Every
+// read and write is bounded by the read timeout and request deadline of the
+// request currently using the connection.
+pub(crate) struct ClientStream<R: Runtime> {
+    inner: Inner<R::Stream>,
+    read_timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    _runtime: PhantomData<fn() -> R>,
+}
+
+enum Inner<S> {
+    Plain(S),
+    #[cfg(feature = "tls")]
+    Tls(Box<TlsStream<S>>),
+}
+
+impl<R: Runtime> ClientStream<R> {
+    pub fn plain(stream: R::Stream) -> Self {
+        Self::new(Inner::Plain(stream))
+    }
+
+    #[cfg(feature = "tls")]
+    pub fn tls(stream: TlsStream<R::Stream>) -> Self {
+        Self::new(Inner::Tls(Box::new(stream)))
+    }
+
+    fn new(inner: Inner<R::Stream>) -> Self {
+        Self {
+            inner,
+            read_timeout: None,
+            deadline: None,
+            _runtime: PhantomData,
+        }
+    }
+
+    pub fn set_timeouts(&mut self, read_timeout: Option<Duration>, deadline: Option<Instant>) {
+        self.read_timeout = read_timeout;
+        self.deadline = deadline;
+    }
+}
+
+impl<R: Runtime> Transport for ClientStream<R> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let limit = Limit::new(self.read_timeout, TimeoutKind::Read, self.deadline);
+        self.set_timeout(limit.duration())?;
+        match &mut self.inner {
+            Inner::Plain(stream) => limit.run::<R, _>(stream.read(buf)).await,
+            #[cfg(feature = "tls")]
+            Inner::Tls(stream) => limit.run::<R, _>(stream.read(buf)).await,
+        }
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let limit = Limit::new(self.read_timeout, TimeoutKind::Write, self.deadline);
+        self.set_timeout(limit.duration())?;
+        match &mut self.inner {
+            Inner::Plain(stream) => limit.run::<R, _>(stream.write_all(buf)).await,
+            #[cfg(feature = "tls")]
+            Inner::Tls(stream) => limit.run::<R, _>(stream.write_all(buf)).await,
+        }
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        let limit = Limit::new(self.read_timeout, TimeoutKind::Write, self.deadline);
+        self.set_timeout(limit.duration())?;
+        match &mut self.inner {
+            Inner::Plain(stream) => limit.run::<R, _>(stream.flush()).await,
+            #[cfg(feature = "tls")]
+            Inner::Tls(stream) => limit.run::<R, _>(stream.flush()).await,
+        }
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        match &mut self.inner {
+            Inner::Plain(stream) => stream.set_timeout(timeout),
+            #[cfg(feature = "tls")]
+            Inner::Tls(stream) => stream.set_timeout(timeout),
+        }
+    }
+}
+
+// The time allowed for one step of a request: the step's own timeout or
+// whatever is left until the request deadline, whichever is shorter
+pub(crate) struct Limit {
+    limit: Option<(Duration, TimeoutKind)>,
+}
+
+impl Limit {
+    pub fn new(step: Option<Duration>, kind: TimeoutKind, deadline: Option<Instant>) -> Self {
+        let step = step.map(|step| (step, kind));
+        let remaining = deadline.map(|deadline| {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            (remaining, TimeoutKind::Total)
+        });
+        let limit = match (step, remaining) {
+            (Some(step), Some(remaining)) if remaining.0 < step.0 => Some(remaining),
+            (Some(step), _) => Some(step),
+            (None, remaining) => remaining,
+        };
+        Self { limit }
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.limit.map(|(duration, _)| duration)
+    }
+
+    // Runs `future`, failing with `HttpError::Timeout` if the limit expires.
+    // Blocking transports report an expired socket timeout as an OS error,
+    // which is translated the same way.
+    pub async fn run<R, T>(self, future: impl Future<Output = io::Result<T>>) -> io::Result<T>
+    where
+        R: Runtime,
+    {
+        let Some((after, kind)) = self.limit else {
+            return future.await;
+        };
+        match timeout::<R, _>(after, future).await {
+            Some(Err(e)) if is_socket_timeout(&e) => Err(HttpError::Timeout { kind, after }.into()),
+            Some(result) => result,
+            None => Err(HttpError::Timeout { kind, after }.into()),
+        }
+    }
+}
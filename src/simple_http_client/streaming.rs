@@ -0,0 +1,128 @@
+use super::error::HttpError;
+use super::reader::{BodyReader, Connection, Head};
+use super::response::HeaderMap;
+use crate::runtime::Transport;
+use bytes::Bytes;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use url::Url;
+
+// A response whose body has not been read yet. The status line and headers
+// are available immediately; the body arrives chunk by chunk from `body`.
+pub struct StreamingResponse {
+    pub version: String,
+    pub status: u16,
+    pub reason: String,
+    pub headers: HeaderMap,
+    pub body: BodyStream,
+    // URLs that answered with a redirect on the way to this response
+    pub redirects: Vec<Url>,
+}
+
+impl StreamingResponse {
+    pub(crate) fn new(head: Head, body: BodyStream) -> Self {
+        Self {
+            version: head.version,
+            status: head.status,
+            reason: head.reason,
+            headers: head.headers,
+            body,
+            redirects: Vec::new(),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    // Turns a 4xx or 5xx response into `HttpError::Status`
+    pub fn error_for_status(self) -> Result<Self, HttpError> {
+        if (400..600).contains(&self.status) {
+            return Err(HttpError::Status(self.status));
+        }
+        Ok(self)
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get("Content-Type")
+    }
+
+    // Announced body size, when the server sent a Content-Length
+    pub fn content_length(&self) -> Option<u64> {
+        self.headers.get("Content-Length")?.trim().parse().ok()
+    }
+}
+
+// The body of a `StreamingResponse` as a `Stream` of byte chunks. Memory use
+// is bounded by a single socket read, regardless of the body size. Once the
+// stream has ended the connection goes back to the client's pool; dropping
+// the stream early closes the connection instead.
+pub struct BodyStream {
+    inner: Pin<Box<dyn Stream<Item = Result<Bytes, HttpError>> + Send>>,
+    trailers: Arc<Mutex<HeaderMap>>,
+}
+
+impl BodyStream {
+    // `on_complete` receives the connection after the last chunk was read
+    pub(crate) fn new<S, F>(conn: Connection<S>, reader: BodyReader, on_complete: F) -> Self
+    where
+        S: Transport,
+        F: FnOnce(Connection<S>) + Send + 'static,
+    {
+        let trailers = Arc::new(Mutex::new(HeaderMap::new()));
+        let state = Some(BodyState {
+            conn,
+            reader,
+            on_complete,
+            trailers: trailers.clone(),
+        });
+
+        let inner = futures_util::stream::unfold(state, |state| async move {
+            let mut state = state?;
+            match state.conn.read_chunk(&mut state.reader).await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(state))),
+                Ok(None) => {
+                    *state.trailers.lock().unwrap() = state.reader.trailers().clone();
+                    (state.on_complete)(state.conn);
+                    None
+                }
+                // The connection is in an unknown state: end the stream after
+                // reporting the error and let the connection be dropped
+                Err(e) => Some((Err(e.into()), None)),
+            }
+        });
+
+        Self {
+            inner: Box::pin(inner),
+            trailers,
+        }
+    }
+
+    // Next chunk of the body, or `None` at its end. A convenience for callers
+    // that do not want to import `StreamExt`.
+    pub async fn next_chunk(&mut self) -> Option<Result<Bytes, HttpError>> {
+        futures_util::StreamExt::next(self).await
+    }
+
+    // Trailer fields of a chunked body; empty until the stream has ended
+    pub fn trailers(&self) -> HeaderMap {
+        self.trailers.lock().unwrap().clone()
+    }
+}
+
+struct BodyState<S, F> {
+    conn: Connection<S>,
+    reader: BodyReader,
+    on_complete: F,
+    trailers: Arc<Mutex<HeaderMap>>,
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Bytes, HttpError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
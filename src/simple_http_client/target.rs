@@ -0,0 +1,44 @@
+use super::error::HttpError;
+use url::Url;
+
+// Builds the request-target for the request line: the path plus the query
+// string, if any. The fragment is never sent to the server. `Url::parse`
+// has already percent-encoded both parts, so they can be used verbatim.
+pub(crate) fn request_target(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+// Value of the Host header: the port is only included when it is not the
+// default for the scheme, which `Url::port` already reports as `None`
+pub(crate) fn host_header(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+// Parses `url` and appends the given query parameters to it. Keys and values
+// are form-urlencoded, so callers can pass raw user input.
+pub fn with_query<I, K, V>(url: &str, params: I) -> Result<Url, HttpError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut url = parse_url(url)?;
+    {
+        let mut pairs = url.query_pairs_mut();
+        for (key, value) in params {
+            pairs.append_pair(key.as_ref(), value.as_ref());
+        }
+    }
+    Ok(url)
+}
+
+pub(crate) fn parse_url(url: &str) -> Result<Url, HttpError> {
+    Ok(Url::parse(url)?)
+}
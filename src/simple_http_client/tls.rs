@@ -0,0 +1,163 @@
+use super::error::HttpError;
+use crate::runtime::Transport;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConnection, RootCertStore};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Trust anchors used to verify `https` servers. The default trusts the
+// Mozilla root store bundled by `webpki-roots`; tests talking to a local
+// server with a self-signed certificate start from `TlsConfig::empty()`.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    roots: RootCertStore,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            roots: RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            },
+        }
+    }
+}
+
+impl TlsConfig {
+    // A configuration that trusts no certificates until roots are added
+    pub fn empty() -> Self {
+        Self {
+            roots: RootCertStore::empty(),
+        }
+    }
+
+    // Trusts a DER encoded certificate
+    pub fn add_root_certificate(&mut self, der: &[u8]) -> Result<(), HttpError> {
+        self.roots
+            .add(CertificateDer::from(der.to_vec()))
+            .map_err(|e| HttpError::Tls(Box::new(e)))
+    }
+
+    // Trusts every certificate in a PEM bundle, returning how many were added
+    pub fn add_root_certificates_pem(&mut self, pem: &[u8]) -> Result<usize, HttpError> {
+        let mut added = 0;
+        for cert in CertificateDer::pem_slice_iter(pem) {
+            let cert = cert.map_err(|e| HttpError::Tls(Box::new(e)))?;
+            self.add_root_certificate(&cert)?;
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    pub(crate) fn client_config(&self) -> Arc<rustls::ClientConfig> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .expect("ring supports the default protocol versions")
+            .with_root_certificates(self.roots.clone())
+            .with_no_client_auth();
+        Arc::new(config)
+    }
+}
+
+// A TLS session on top of any `Transport`. rustls does the cryptography on
+// in-memory buffers; this type shuttles the encrypted records to and from
+// the underlying socket, so it works the same on every runtime.
+pub(crate) struct TlsStream<S> {
+    inner: S,
+    conn: ClientConnection,
+}
+
+impl<S: Transport> TlsStream<S> {
+    // Performs the TLS handshake for `host` over an established connection
+    pub async fn connect(
+        inner: S,
+        host: &str,
+        config: Arc<rustls::ClientConfig>,
+    ) -> io::Result<Self> {
+        let server_name = ServerName::try_from(host.to_string()).map_err(tls_error)?;
+        let conn = ClientConnection::new(config, server_name).map_err(tls_error)?;
+        let mut stream = Self { inner, conn };
+
+        while stream.conn.is_handshaking() {
+            stream.write_records().await?;
+            if stream.conn.is_handshaking()
+                && stream.conn.wants_read()
+                && !stream.read_records().await?
+            {
+                let eof = io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed during TLS handshake",
+                );
+                return Err(tls_error(eof));
+            }
+        }
+        stream.write_records().await?;
+        Ok(stream)
+    }
+
+    // Sends every pending TLS record to the socket
+    async fn write_records(&mut self) -> io::Result<()> {
+        while self.conn.wants_write() {
+            let mut records = Vec::new();
+            self.conn.write_tls(&mut records)?;
+            self.inner.write_all(&records).await?;
+        }
+        Ok(())
+    }
+
+    // Feeds one read worth of records from the socket to rustls. Returns
+    // false once the socket has reached EOF.
+    async fn read_records(&mut self) -> io::Result<bool> {
+        let mut buf = vec![0u8; 16 * 1024];
+        let n = self.inner.read(&mut buf).await?;
+        let mut records = &buf[..n];
+        loop {
+            // An empty slice tells rustls that the peer closed the socket
+            self.conn.read_tls(&mut records)?;
+            self.conn.process_new_packets().map_err(tls_error)?;
+            if records.is_empty() {
+                return Ok(n > 0);
+            }
+        }
+    }
+}
+
+impl<S: Transport> Transport for TlsStream<S> {
+    async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.conn.reader().read(buf) {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                // Many servers close the socket without a close_notify alert.
+                // HTTP framing detects truncated bodies, so treat it as EOF.
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(0),
+                Err(e) => return Err(e),
+            }
+            self.read_records().await?;
+            // Reading may produce records to send back, e.g. key updates
+            self.write_records().await?;
+        }
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.conn.writer().write_all(buf)?;
+        self.write_records().await
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        self.write_records().await?;
+        self.inner.flush().await
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.inner.set_timeout(timeout)
+    }
+}
+
+// Wraps a TLS failure so that it surfaces as `HttpError::Tls`
+fn tls_error(e: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    HttpError::Tls(Box::new(e)).into()
+}
@@ -0,0 +1,327 @@
+use super::request::Request;
+use super::response::Response;
+use crate::runtime::Runtime;
+use bytes::Bytes;
+use futures_util::stream::{self, Stream};
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Serves files below a root directory. Bodies are streamed from disk in
+// chunks on the runtime's blocking threads, so large files never sit in
+// memory. Supports conditional requests (`If-None-Match`,
+// `If-Modified-Since`) and single byte ranges (`Range`, `If-Range`).
+#[derive(Debug, Clone)]
+pub struct StaticFiles {
+    root: PathBuf,
+}
+
+// Bytes read from disk per body chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+impl StaticFiles {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    // Answers `request` with the file at `path`, relative to the root.
+    // Paths that would leave the root are answered with 404.
+    pub async fn serve<R: Runtime>(&self, request: &Request, path: &str) -> Response {
+        let mut file_path = self.root.clone();
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            if component == ".." || component == "." || component.contains(['\\', ':', '\0']) {
+                return Response::new(404);
+            }
+            file_path.push(component);
+        }
+
+        let opened = R::spawn_blocking(move || open(file_path)).await;
+        let (file, metadata, file_path) = match opened {
+            // Relative links in an index page only resolve below a '/'
+            Ok(Opened::Index(..)) if !request.path().ends_with('/') => {
+                return Response::redirect(301, &format!("{}/", request.path()));
+            }
+            Ok(Opened::File(file, metadata, path) | Opened::Index(file, metadata, path)) => {
+                (file, metadata, path)
+            }
+            Ok(Opened::Directory) => return Response::new(404),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Response::new(404),
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => return Response::new(403),
+            Err(_) => return Response::new(500),
+        };
+
+        let length = metadata.len();
+        let modified = metadata.modified().ok();
+        let etag = entity_tag(length, modified);
+        let last_modified = modified.map(http_date);
+        let with_validators = |response: Response| {
+            let response = response.header("ETag", etag.clone());
+            match &last_modified {
+                Some(date) => response.header("Last-Modified", date.clone()),
+                None => response,
+            }
+        };
+
+        if not_modified(request, &etag, modified) {
+            return with_validators(Response::new(304));
+        }
+        let response = with_validators(Response::new(200))
+            .header("Content-Type", content_type(&file_path))
+            .header("Accept-Ranges", "bytes");
+
+        let range = request
+            .headers
+            .get("Range")
+            .filter(|_| range_applies(request, &etag, last_modified.as_deref()))
+            .and_then(|range| parse_range(range, length));
+        match range {
+            Some(Ok((start, end))) => response
+                .status(206)
+                .header(
+                    "Content-Range",
+                    format!("bytes {}-{}/{}", start, end, length),
+                )
+                .header("Content-Length", (end - start + 1).to_string())
+                .stream(file_stream::<R>(file, start, end - start + 1)),
+            Some(Err(())) => {
+                Response::new(416).header("Content-Range", format!("bytes */{}", length))
+            }
+            None => response
+                .header("Content-Length", length.to_string())
+                .stream(file_stream::<R>(file, 0, length)),
+        }
+    }
+}
+
+enum Opened {
+    File(File, Metadata, PathBuf),
+    // The `index.html` of a directory
+    Index(File, Metadata, PathBuf),
+    // A directory without an index
+    Directory,
+}
+
+fn open(path: PathBuf) -> io::Result<Opened> {
+    if path.is_dir() {
+        let index = path.join("index.html");
+        if !index.is_file() {
+            return Ok(Opened::Directory);
+        }
+        let file = File::open(&index)?;
+        let metadata = file.metadata()?;
+        return Ok(Opened::Index(file, metadata, index));
+    }
+    let file = File::open(&path)?;
+    let metadata = file.metadata()?;
+    Ok(Opened::File(file, metadata, path))
+}
+
+// `length` bytes of `file` from offset `start`, read chunk by chunk
+fn file_stream<R: Runtime>(
+    file: File,
+    start: u64,
+    length: u64,
+) -> impl Stream<Item = io::Result<Bytes>> + Send + 'static {
+    stream::try_unfold(
+        (file, start, length),
+        |(mut file, offset, remaining)| async move {
+            if remaining == 0 {
+                return Ok(None);
+            }
+            let (file, chunk) = R::spawn_blocking(move || {
+                let mut chunk = vec![0; remaining.min(CHUNK_SIZE as u64) as usize];
+                file.seek(SeekFrom::Start(offset))?;
+                let n = file.read(&mut chunk)?;
+                chunk.truncate(n);
+                Ok::<_, io::Error>((file, chunk))
+            })
+            .await?;
+            // The file shrank after its length was sent
+            if chunk.is_empty() {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let n = chunk.len() as u64;
+            Ok(Some((
+                Bytes::from(chunk),
+                (file, offset + n, remaining - n),
+            )))
+        },
+    )
+}
+
+// Whether the client's cached copy is current (RFC 9110 section 13.2.2)
+fn not_modified(request: &Request, etag: &str, modified: Option<SystemTime>) -> bool {
+    if let Some(tags) = request.headers.get("If-None-Match") {
+        return tags.trim() == "*" || tags.split(',').any(|tag| weak_eq(tag, etag));
+    }
+    let since = request
+        .headers
+        .get("If-Modified-Since")
+        .and_then(parse_http_date);
+    match (since, modified) {
+        (Some(since), Some(modified)) => unix_secs(modified) <= since,
+        _ => false,
+    }
+}
+
+// Whether a Range header may be honoured: `If-Range` must name the current
+// entity tag or modification date, else the whole file is sent
+fn range_applies(request: &Request, etag: &str, last_modified: Option<&str>) -> bool {
+    match request.headers.get("If-Range") {
+        None => true,
+        Some(validator) => validator == etag || Some(validator) == last_modified,
+    }
+}
+
+// The inclusive byte range a `Range` header selects from `length` bytes.
+// `None` means the header is ignored, e.g. for multiple ranges, and
+// `Some(Err(()))` that the range is not satisfiable.
+pub(crate) fn parse_range(value: &str, length: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (first, last) = spec.split_once('-')?;
+    let (first, last) = (first.trim(), last.trim());
+    let range = if first.is_empty() {
+        let suffix: u64 = last.parse().ok()?;
+        match suffix {
+            0 => Err(()),
+            _ if length == 0 => Err(()),
+            _ => Ok((length.saturating_sub(suffix), length - 1)),
+        }
+    } else {
+        let first: u64 = first.parse().ok()?;
+        let last = match last {
+            "" => u64::MAX,
+            last => last.parse().ok().filter(|last| *last >= first)?,
+        };
+        match first < length {
+            true => Ok((first, last.min(length - 1))),
+            false => Err(()),
+        }
+    };
+    Some(range)
+}
+
+fn entity_tag(length: u64, modified: Option<SystemTime>) -> String {
+    let nanos = modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos());
+    format!("\"{:x}-{:x}\"", length, nanos)
+}
+
+// Compares entity tags ignoring the weak `W/` prefix
+fn weak_eq(a: &str, b: &str) -> bool {
+    let strip = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    strip(a) == strip(b)
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// Formats `time` as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+pub(crate) fn http_date(time: SystemTime) -> String {
+    let secs = unix_secs(time);
+    let days = secs / 86400;
+    let (year, month, day) = civil_from_days(days as i64);
+    let seconds = secs % 86400;
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[((days + 4) % 7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// Parses an IMF-fixdate into seconds since the Unix epoch. The obsolete
+// RFC 850 and asctime formats are not accepted.
+pub(crate) fn parse_http_date(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let (_weekday, day, month, year, time, zone) = (
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+    );
+    if zone != "GMT" || parts.next().is_some() {
+        return None;
+    }
+    let day: u64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u64 + 1;
+    let year: i64 = year.parse().ok()?;
+    let mut time = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=31).contains(&day) || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+// Days since 1970-01-01 to (year, month, day), after Howard Hinnant's
+// `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, u64, u64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// The inverse of `civil_from_days`
+fn days_from_civil(year: i64, month: u64, day: u64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// The Content-Type for a file, guessed from its extension
+fn content_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,206 @@
+use super::{Request, Response, Router, Server};
+use crate::runtime::Runtime;
+use bytes::Bytes;
+use futures_util::stream;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+// A local stand-in for httpbin.org, so tests and examples work offline.
+// Implements the endpoints this crate uses, with httpbin's JSON layout:
+//
+// - `GET /get` and `GET /headers` echo the request
+// - `POST /post` also echoes the body as `data` (`json` is always null)
+// - `/status/{code}` answers with that status and an empty body
+// - `/delay/{n}` answers like `/get` after n seconds (at most 10)
+// - `/stream/{n}` sends n JSON lines as a chunked body (at most 100)
+// - `/redirect/{n}` redirects n times before ending at `/get`
+//
+// The server binds an ephemeral port on 127.0.0.1 and runs on any runtime,
+// e.g. `HttpBin::start::<SmolRuntime>()`. It lives until the process exits.
+pub struct HttpBin {
+    addr: SocketAddr,
+}
+
+impl HttpBin {
+    pub async fn start<R: Runtime>() -> io::Result<Self> {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+        let server = Server::<R>::bind(addr, router::<R>()).await?;
+        let addr = server.local_addr()?;
+        R::spawn(async move {
+            let _ = server.run().await;
+        });
+        Ok(Self { addr })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // The full URL of `path` on this server, e.g. `url("/get")`
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+fn router<R: Runtime>() -> Router {
+    Router::new()
+        .get("/get", |request: Request| async move {
+            Response::json(echo(&request, false))
+        })
+        .post("/post", |request: Request| async move {
+            Response::json(echo(&request, true))
+        })
+        .get("/headers", |request: Request| async move {
+            Response::json(format!("{{\"headers\": {}}}", headers_json(&request)))
+        })
+        .any("/status/{code}", |request: Request| async move {
+            match number(&request, "code", 999).filter(|code| *code >= 100) {
+                Some(code) => Response::new(code as u16),
+                None => Response::new(400),
+            }
+        })
+        .get("/delay/{n}", |request: Request| async move {
+            match number(&request, "n", 10) {
+                Some(seconds) => {
+                    R::sleep(Duration::from_secs(seconds)).await;
+                    Response::json(echo(&request, false))
+                }
+                None => Response::new(400),
+            }
+        })
+        .get("/stream/{n}", |request: Request| async move {
+            match number(&request, "n", 100) {
+                Some(lines) => {
+                    let lines: Vec<io::Result<Bytes>> = (0..lines)
+                        .map(|id| Ok(format!("{}\n", stream_line(&request, id)).into()))
+                        .collect();
+                    Response::json("").stream(stream::iter(lines))
+                }
+                None => Response::new(400),
+            }
+        })
+        .get("/redirect/{n}", |request: Request| async move {
+            match number(&request, "n", 100) {
+                Some(1) => Response::redirect(302, "/get"),
+                Some(n) if n > 1 => Response::redirect(302, &format!("/redirect/{}", n - 1)),
+                _ => Response::new(400),
+            }
+        })
+}
+
+// Path parameter `name` as a number no larger than `max`
+fn number(request: &Request, name: &str, max: u64) -> Option<u64> {
+    request.param(name)?.parse().ok().filter(|n| *n <= max)
+}
+
+// The JSON httpbin answers `/get` and `/post` with
+fn echo(request: &Request, with_body: bool) -> String {
+    let mut fields = vec![
+        ("args", query_json(request.query())),
+        ("headers", headers_json(request)),
+        ("origin", json_string(&request.peer.ip().to_string())),
+        ("url", json_string(&full_url(request))),
+    ];
+    if with_body {
+        let content_type = request.headers.get("Content-Type").unwrap_or("");
+        let (data, form) = if content_type.starts_with("application/x-www-form-urlencoded") {
+            (
+                String::new(),
+                query_json(&String::from_utf8_lossy(&request.body)),
+            )
+        } else {
+            (
+                String::from_utf8_lossy(&request.body).into_owned(),
+                "{}".to_string(),
+            )
+        };
+        fields.push(("data", json_string(&data)));
+        fields.push(("files", "{}".to_string()));
+        fields.push(("form", form));
+        fields.push(("json", "null".to_string()));
+    }
+    fields.sort_by_key(|(name, _)| *name);
+    json_object(fields.iter().map(|(name, value)| (*name, value.as_str())))
+}
+
+fn stream_line(request: &Request, id: u64) -> String {
+    let fields = [
+        ("args", query_json(request.query())),
+        ("headers", headers_json(request)),
+        ("id", id.to_string()),
+        ("origin", json_string(&request.peer.ip().to_string())),
+        ("url", json_string(&full_url(request))),
+    ];
+    json_object(fields.iter().map(|(name, value)| (*name, value.as_str())))
+}
+
+fn full_url(request: &Request) -> String {
+    let host = request.headers.get("Host").unwrap_or("127.0.0.1");
+    format!("http://{}{}", host, request.target)
+}
+
+fn headers_json(request: &Request) -> String {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for (name, value) in request.headers.iter() {
+        match headers
+            .iter_mut()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            Some((_, joined)) => {
+                joined.push(',');
+                joined.push_str(value);
+            }
+            None => headers.push((name.to_string(), value.to_string())),
+        }
+    }
+    let values: Vec<String> = headers
+        .iter()
+        .map(|(_, value)| json_string(value))
+        .collect();
+    json_object(
+        headers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .zip(values.iter().map(String::as_str)),
+    )
+}
+
+fn query_json(query: &str) -> String {
+    let pairs: Vec<(String, String)> = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+    let values: Vec<String> = pairs.iter().map(|(_, value)| json_string(value)).collect();
+    json_object(
+        pairs
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .zip(values.iter().map(String::as_str)),
+    )
+}
+
+// `{"name": value, ...}` from already encoded values
+fn json_object<'a>(fields: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    let fields: Vec<String> = fields
+        .map(|(name, value)| format!("{}: {}", json_string(name), value))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,22 @@
+// A minimal async HTTP/1.1 server, the counterpart of `simple_http_client`.
+// Requests are dispatched by a `Router` to async handlers:
+//
+//     let router = Router::new().get("/hello/{name}", |request: Request| async move {
+//         Response::text(format!("Hello, {}!", request.param("name").unwrap_or("")))
+//     });
+//     let server = Server::<TokioRuntime>::bind(addr, router).await?;
+//     server.run().await?;
+pub(crate) mod files;
+mod httpbin;
+mod request;
+mod response;
+mod router;
+mod server;
+
+pub use crate::simple_http_client::{HeaderMap, Method};
+pub use files::StaticFiles;
+pub use httpbin::HttpBin;
+pub use request::Request;
+pub use response::{Response, ResponseBody};
+pub use router::Router;
+pub use server::{Server, ServerConfig, ShutdownHandle};
@@ -0,0 +1,247 @@
+use super::server::ServerConfig;
+use crate::runtime::Transport;
+use crate::simple_http_client::reader::ChunkedDecoder;
+use crate::simple_http_client::response::is_valid_header;
+use crate::simple_http_client::{HeaderMap, Method};
+use bytes::{Bytes, BytesMut};
+use percent_encoding::percent_decode_str;
+use std::io;
+use std::net::SocketAddr;
+
+// A request as handed to a handler, with its body read completely
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: Method,
+    // The request target as sent, e.g. `/search?q=fox`
+    pub target: String,
+    pub version: String,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    // The address of the connected client
+    pub peer: SocketAddr,
+    // Path parameters captured by the matching route
+    pub(crate) params: Vec<(String, String)>,
+}
+
+impl Request {
+    // The target without its query string
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or("/")
+    }
+
+    // The raw query string without the leading '?', or ""
+    pub fn query(&self) -> &str {
+        self.target.split_once('?').map_or("", |(_, query)| query)
+    }
+
+    // The decoded query pairs, in order
+    pub fn query_pairs(&self) -> Vec<(String, String)> {
+        url::form_urlencoded::parse(self.query().as_bytes())
+            .into_owned()
+            .collect()
+    }
+
+    // The percent-decoded value of path parameter `{name}`
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // Whether the connection stays open after the response
+    pub(crate) fn keep_alive(&self) -> bool {
+        let connection = self.headers.get("Connection").unwrap_or("");
+        if has_token(connection, "close") {
+            return false;
+        }
+        self.version != "HTTP/1.0" || has_token(connection, "keep-alive")
+    }
+}
+
+// Why no request could be read from a connection
+#[derive(Debug)]
+pub(crate) enum ReadError {
+    Io(io::Error),
+    // The request is unacceptable; answer with this status and close
+    Reject(u16),
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+// Reads the next request from `stream`; `buf` carries bytes that arrived
+// beyond the previous request. Returns `None` once the client has closed
+// the connection between requests.
+pub(crate) async fn read_request<S: Transport>(
+    stream: &mut S,
+    buf: &mut BytesMut,
+    peer: SocketAddr,
+    config: &ServerConfig,
+) -> Result<Option<Request>, ReadError> {
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > config.max_head_size {
+            return Err(ReadError::Reject(431));
+        }
+        if !fill(stream, buf).await? {
+            return match buf.is_empty() {
+                true => Ok(None),
+                false => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            };
+        }
+    };
+    let head = buf.split_to(head_end);
+    let head = std::str::from_utf8(&head[..head_end - 4]).map_err(|_| ReadError::Reject(400))?;
+
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split(' ');
+    let (Some(method), Some(target), Some(version), None) = (
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+    ) else {
+        return Err(ReadError::Reject(400));
+    };
+    let method = parse_method(method).ok_or(ReadError::Reject(501))?;
+    if !version.starts_with("HTTP/1.") {
+        return Err(ReadError::Reject(505));
+    }
+    let mut headers = HeaderMap::new();
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(ReadError::Reject(400))?;
+        let value = value.trim();
+        // Whitespace before the colon must be rejected (RFC 9112 section
+        // 5.1): a server that trims it may disagree with a proxy about the
+        // request's framing
+        if !is_valid_header(name, value) {
+            return Err(ReadError::Reject(400));
+        }
+        headers.append(name, value);
+    }
+
+    let length = content_length(&headers)?;
+    // Refuse an oversized upload before inviting the client to send it
+    let chunked = headers.contains_key("Transfer-Encoding");
+    if !chunked && length.is_some_and(|length| length > config.max_body_size) {
+        return Err(ReadError::Reject(413));
+    }
+    if headers
+        .get("Expect")
+        .is_some_and(|expect| expect.eq_ignore_ascii_case("100-continue"))
+    {
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await?;
+        stream.flush().await?;
+    }
+    let body = read_body(stream, buf, &headers, length, config.max_body_size).await?;
+    Ok(Some(Request {
+        method,
+        target: target.to_string(),
+        version: version.to_string(),
+        headers,
+        body,
+        peer,
+        params: Vec::new(),
+    }))
+}
+
+// Reads the body framed by Transfer-Encoding or, failing that, by the
+// request's Content-Length `length`
+async fn read_body<S: Transport>(
+    stream: &mut S,
+    buf: &mut BytesMut,
+    headers: &HeaderMap,
+    length: Option<usize>,
+    max_body_size: usize,
+) -> Result<Bytes, ReadError> {
+    if let Some(coding) = headers.get("Transfer-Encoding") {
+        // Compressed request bodies are not supported
+        if !coding.eq_ignore_ascii_case("chunked") {
+            return Err(ReadError::Reject(501));
+        }
+        let mut decoder = ChunkedDecoder::default();
+        let mut body = BytesMut::new();
+        while !decoder
+            .decode(buf, &mut body)
+            .map_err(|_| ReadError::Reject(400))?
+        {
+            if body.len() > max_body_size {
+                return Err(ReadError::Reject(413));
+            }
+            if !fill(stream, buf).await? {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+        }
+        if body.len() > max_body_size {
+            return Err(ReadError::Reject(413));
+        }
+        return Ok(body.freeze());
+    }
+
+    let length = length.unwrap_or(0);
+    if length > max_body_size {
+        return Err(ReadError::Reject(413));
+    }
+    while buf.len() < length {
+        if !fill(stream, buf).await? {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+    }
+    Ok(buf.split_to(length).freeze())
+}
+
+// The Content-Length of a request. Repeated headers must agree, as a
+// request that two parties could frame differently is rejected with 400.
+fn content_length(headers: &HeaderMap) -> Result<Option<usize>, ReadError> {
+    let mut length = None;
+    for value in headers.get_all("Content-Length") {
+        let value: usize = value.parse().map_err(|_| ReadError::Reject(400))?;
+        if length.is_some_and(|length| length != value) {
+            return Err(ReadError::Reject(400));
+        }
+        length = Some(value);
+    }
+    Ok(length)
+}
+
+// Reads more bytes into `buf`; false once the peer has closed
+async fn fill<S: Transport>(stream: &mut S, buf: &mut BytesMut) -> io::Result<bool> {
+    let mut chunk = [0u8; 8192];
+    let n = stream.read(&mut chunk).await?;
+    buf.extend_from_slice(&chunk[..n]);
+    Ok(n > 0)
+}
+
+fn parse_method(method: &str) -> Option<Method> {
+    Some(match method {
+        "GET" => Method::Get,
+        "HEAD" => Method::Head,
+        "POST" => Method::Post,
+        "PUT" => Method::Put,
+        "PATCH" => Method::Patch,
+        "DELETE" => Method::Delete,
+        "OPTIONS" => Method::Options,
+        _ => return None,
+    })
+}
+
+// Whether the comma separated header `value` lists `token`
+fn has_token(value: &str, token: &str) -> bool {
+    value
+        .split(',')
+        .any(|part| part.trim().eq_ignore_ascii_case(token))
+}
+
+// The non-empty, percent-decoded segments of a request path
+pub(crate) fn path_segments(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect()
+}
@@ -0,0 +1,205 @@
+use crate::runtime::Transport;
+use crate::simple_http_client::response::is_valid_header;
+use crate::simple_http_client::HeaderMap;
+use bytes::Bytes;
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use std::fmt;
+use std::io;
+
+// A response returned by a handler
+pub struct Response {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: ResponseBody,
+}
+
+pub enum ResponseBody {
+    Full(Bytes),
+    // Sent as it is produced: chunked, or as is when the response carries
+    // a Content-Length header
+    Stream(BoxStream<'static, io::Result<Bytes>>),
+}
+
+impl fmt::Debug for ResponseBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseBody::Full(body) => f.debug_tuple("Full").field(body).finish(),
+            ResponseBody::Stream(_) => f.write_str("Stream"),
+        }
+    }
+}
+
+impl Response {
+    // An empty response with `status`
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: ResponseBody::Full(Bytes::new()),
+        }
+    }
+
+    // 200 with a `text/plain` body
+    pub fn text(body: impl Into<String>) -> Self {
+        Self::new(200)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(body.into())
+    }
+
+    // 200 with an `application/json` body
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::new(200)
+            .header("Content-Type", "application/json")
+            .body(body.into())
+    }
+
+    // A redirect with `status` (301, 302, 303, 307 or 308) to `location`
+    pub fn redirect(status: u16, location: &str) -> Self {
+        Self::new(status).header("Location", location)
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    // Replaces any previous values of the header. A response with a header
+    // name that is not a token or a value with CR, LF or NUL in it is sent
+    // as an empty 500 instead.
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = ResponseBody::Full(body.into());
+        self
+    }
+
+    pub fn stream<S>(mut self, stream: S) -> Self
+    where
+        S: Stream<Item = io::Result<Bytes>> + Send + 'static,
+    {
+        self.body = ResponseBody::Stream(stream.boxed());
+        self
+    }
+}
+
+// Headers the server derives from the body and connection itself
+const FRAMING_HEADERS: [&str; 3] = ["Connection", "Content-Length", "Transfer-Encoding"];
+
+// Writes `response`, leaving out the body for HEAD requests. `close` tells
+// the client the connection ends after this response.
+pub(crate) async fn write_response<S: Transport>(
+    stream: &mut S,
+    response: Response,
+    head_only: bool,
+    close: bool,
+) -> io::Result<()> {
+    // Such a header could end its line early and add headers of its own,
+    // e.g. when a handler redirects to a URL taken from the request
+    let valid = response
+        .headers
+        .iter()
+        .all(|(name, value)| is_valid_header(name, value));
+    let response = match valid {
+        true => response,
+        false => Response::new(500),
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        response.status,
+        reason(response.status)
+    );
+    for (name, value) in response.headers.iter() {
+        if !FRAMING_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    if close {
+        head.push_str("Connection: close\r\n");
+    }
+    // 1xx, 204 and 304 responses never have a body (RFC 9112 section 6.3)
+    let bodiless = matches!(response.status, 100..=199 | 204 | 304);
+    let length = response.headers.get("Content-Length").map(str::to_string);
+
+    match response.body {
+        ResponseBody::Full(body) => {
+            if !bodiless {
+                head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+            }
+            head.push_str("\r\n");
+            stream.write_all(head.as_bytes()).await?;
+            if !head_only && !bodiless {
+                stream.write_all(&body).await?;
+            }
+        }
+        ResponseBody::Stream(mut body) => {
+            let chunked = length.is_none() && !bodiless;
+            match &length {
+                Some(length) => head.push_str(&format!("Content-Length: {}\r\n", length)),
+                None if chunked => head.push_str("Transfer-Encoding: chunked\r\n"),
+                None => {}
+            }
+            head.push_str("\r\n");
+            stream.write_all(head.as_bytes()).await?;
+            if !head_only && !bodiless {
+                while let Some(chunk) = body.next().await {
+                    let chunk = chunk?;
+                    // An empty chunk would end a chunked body early
+                    if chunk.is_empty() {
+                        continue;
+                    }
+                    if chunked {
+                        stream
+                            .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                            .await?;
+                        stream.write_all(&chunk).await?;
+                        stream.write_all(b"\r\n").await?;
+                    } else {
+                        stream.write_all(&chunk).await?;
+                    }
+                }
+                if chunked {
+                    stream.write_all(b"0\r\n\r\n").await?;
+                }
+            }
+        }
+    }
+    stream.flush().await
+}
+
+pub(crate) fn reason(status: u16) -> &'static str {
+    match status {
+        100 => "Continue",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Content Too Large",
+        416 => "Range Not Satisfiable",
+        418 => "I'm a teapot",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        _ => "",
+    }
+}
@@ -0,0 +1,184 @@
+use super::files::StaticFiles;
+use super::request::{path_segments, Request};
+use super::response::Response;
+use crate::runtime::Runtime;
+use crate::simple_http_client::Method;
+use futures_util::future::{BoxFuture, FutureExt};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+type Handler = Arc<dyn Fn(Request) -> BoxFuture<'static, Response> + Send + Sync>;
+
+// Maps method and path to handlers. Patterns are literal segments,
+// `{name}` parameters matching one segment, e.g. `/users/{id}/posts`, and
+// a final `{*name}` matching the rest of the path, e.g. `/files/{*path}`.
+// Routes are tried in the order they were added; HEAD requests fall back
+// to GET routes.
+#[derive(Clone, Default)]
+pub struct Router {
+    routes: Vec<Route>,
+}
+
+#[derive(Clone)]
+struct Route {
+    // `None` matches every method
+    method: Option<Method>,
+    segments: Vec<Segment>,
+    handler: Handler,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    // Matches the remaining segments, possibly none
+    Rest(String),
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a route; `handler` is usually an async closure such as
+    // `|request: Request| async move { Response::text("hi") }`
+    pub fn route<F, Fut>(self, method: Method, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        self.add(Some(method), pattern, handler)
+    }
+
+    // Adds a route that matches every method
+    pub fn any<F, Fut>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        self.add(None, pattern, handler)
+    }
+
+    pub fn get<F, Fut>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        self.route(Method::Get, pattern, handler)
+    }
+
+    pub fn post<F, Fut>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        self.route(Method::Post, pattern, handler)
+    }
+
+    // Serves the files below `root` under `prefix`, with `index.html` for
+    // directories, e.g. `static_dir::<TokioRuntime>("/assets", "public")`
+    pub fn static_dir<R: Runtime>(self, prefix: &str, root: impl Into<PathBuf>) -> Self {
+        let files = Arc::new(StaticFiles::new(root));
+        let pattern = format!("{}/{{*path}}", prefix.trim_end_matches('/'));
+        self.get(&pattern, move |request: Request| {
+            let files = files.clone();
+            async move {
+                let path = request.param("path").unwrap_or("").to_string();
+                files.serve::<R>(&request, &path).await
+            }
+        })
+    }
+
+    fn add<F, Fut>(mut self, method: Option<Method>, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        let segments = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment.strip_prefix('{') {
+                Some(name) => match name.trim_end_matches('}').strip_prefix('*') {
+                    Some(rest) => Segment::Rest(rest.to_string()),
+                    None => Segment::Param(name.trim_end_matches('}').to_string()),
+                },
+                None => Segment::Literal(segment.to_string()),
+            })
+            .collect();
+        self.routes.push(Route {
+            method,
+            segments,
+            handler: Arc::new(move |request| handler(request).boxed()),
+        });
+        self
+    }
+
+    // Runs the handler of the first matching route. Answers 404 when no
+    // pattern matches the path and 405 when none allows the method.
+    pub async fn handle(&self, mut request: Request) -> Response {
+        let segments = path_segments(request.path());
+        let matches = |method: Method| {
+            self.routes.iter().find_map(|route| {
+                if route.method.is_some_and(|m| m != method) {
+                    return None;
+                }
+                route.params(&segments).map(|params| (route, params))
+            })
+        };
+        let found = match matches(request.method) {
+            None if request.method == Method::Head => matches(Method::Get),
+            found => found,
+        };
+        if let Some((route, params)) = found {
+            request.params = params;
+            return (route.handler)(request).await;
+        }
+
+        let mut allowed: Vec<&str> = Vec::new();
+        for route in &self.routes {
+            if let (Some(method), Some(_)) = (route.method, route.params(&segments)) {
+                if !allowed.contains(&method.as_str()) {
+                    allowed.push(method.as_str());
+                }
+            }
+        }
+        match allowed.is_empty() {
+            true => Response::new(404),
+            false => Response::new(405).header("Allow", allowed.join(", ")),
+        }
+    }
+}
+
+impl Route {
+    // The captured parameters if the route's pattern matches `segments`
+    fn params(&self, segments: &[String]) -> Option<Vec<(String, String)>> {
+        let mut params = Vec::new();
+        for (i, pattern) in self.segments.iter().enumerate() {
+            match (pattern, segments.get(i)) {
+                (Segment::Rest(name), _) => {
+                    params.push((name.clone(), segments[i.min(segments.len())..].join("/")));
+                    return Some(params);
+                }
+                (Segment::Literal(literal), Some(segment)) if literal == segment => {}
+                (Segment::Param(name), Some(segment)) => {
+                    params.push((name.clone(), segment.clone()))
+                }
+                _ => return None,
+            }
+        }
+        (segments.len() == self.segments.len()).then_some(params)
+    }
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let routes: Vec<_> = self
+            .routes
+            .iter()
+            .map(|route| (route.method, &route.segments))
+            .collect();
+        f.debug_struct("Router").field("routes", &routes).finish()
+    }
+}
@@ -0,0 +1,285 @@
+use super::request::{read_request, ReadError};
+use super::response::{write_response, Response};
+use super::router::Router;
+use crate::runtime::{is_socket_timeout, timeout, DefaultRuntime, Listener, Runtime, Transport};
+use crate::simple_http_client::Method;
+use async_lock::{Semaphore, SemaphoreGuardArc};
+use bytes::BytesMut;
+use futures_util::future::{AbortHandle, AbortRegistration, Abortable};
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Limits applied to every connection of a `Server`
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    // Larger request heads are answered with 431
+    pub max_head_size: usize,
+    // Larger request bodies are answered with 413
+    pub max_body_size: usize,
+    // Connections served at once; further clients wait in the listen backlog
+    pub max_connections: usize,
+    // Connections that take longer to send their next request are closed
+    pub idle_timeout: Duration,
+    // How long requests in flight may take to finish after a shutdown
+    // before their connections are aborted
+    pub shutdown_timeout: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_head_size: 64 * 1024,
+            max_body_size: 16 * 1024 * 1024,
+            max_connections: 1024,
+            idle_timeout: Duration::from_secs(60),
+            shutdown_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+// An HTTP/1.1 server that serves each connection in its own task on
+// runtime `R`. Connections are kept alive until the client closes them,
+// they idle for `ServerConfig::idle_timeout` or the server shuts down.
+pub struct Server<R: Runtime = DefaultRuntime> {
+    listener: R::Listener,
+    router: Arc<Router>,
+    config: Arc<ServerConfig>,
+    shutdown: AbortHandle,
+    registration: AbortRegistration,
+}
+
+// Stops the `Server` it was taken from. Cloning is cheap.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle(AbortHandle);
+
+impl ShutdownHandle {
+    // Starts a graceful shutdown: the server stops accepting, closes idle
+    // connections and lets requests in flight finish within
+    // `ServerConfig::shutdown_timeout` before aborting them. `Server::run`
+    // returns once this is done. On a runtime whose reads block, such as
+    // `StdRuntime`, idle connections close when their idle timeout expires.
+    pub fn shutdown(&self) {
+        self.0.abort();
+    }
+}
+
+impl<R: Runtime> Server<R> {
+    pub async fn bind(addr: SocketAddr, router: Router) -> io::Result<Self> {
+        Self::bind_with_config(addr, router, ServerConfig::default()).await
+    }
+
+    pub async fn bind_with_config(
+        addr: SocketAddr,
+        router: Router,
+        config: ServerConfig,
+    ) -> io::Result<Self> {
+        let (shutdown, registration) = AbortHandle::new_pair();
+        Ok(Self {
+            listener: R::bind(addr).await?,
+            router: Arc::new(router),
+            config: Arc::new(config),
+            shutdown,
+            registration,
+        })
+    }
+
+    // The bound address, with the actual port when bound to port 0
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle(self.shutdown.clone())
+    }
+
+    // Serves connections until shut down through a `ShutdownHandle`, or
+    // until accepting fails for a reason other than a client giving up early
+    pub async fn run(self) -> io::Result<()> {
+        let connections = Arc::new(Connections::default());
+        let accepting = Abortable::new(
+            accept_loop::<R>(&self.listener, &self.router, &self.config, &connections),
+            self.registration,
+        );
+        if let Ok(Err(e)) = accepting.await {
+            return Err(e);
+        }
+        drop(self.listener);
+
+        // No waker fires when the last connection closes, so poll for it
+        connections.drain();
+        let deadline = Instant::now() + self.config.shutdown_timeout;
+        while !connections.is_empty() {
+            if Instant::now() >= deadline {
+                connections.abort_all();
+                break;
+            }
+            R::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+}
+
+// How often `run` checks whether a shutdown has drained all connections
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+async fn accept_loop<R: Runtime>(
+    listener: &R::Listener,
+    router: &Arc<Router>,
+    config: &Arc<ServerConfig>,
+    connections: &Arc<Connections>,
+) -> io::Result<()> {
+    let permits = Arc::new(Semaphore::new(config.max_connections.max(1)));
+    loop {
+        // Waiting for a permit before accepting leaves further clients in
+        // the listen backlog instead of accepting and stalling them
+        let permit = permits.acquire_arc().await;
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) if is_transient(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        let (abort, registration) = AbortHandle::new_pair();
+        let id = connections.add(abort);
+        let connection = Connection {
+            id,
+            router: router.clone(),
+            config: config.clone(),
+            connections: connections.clone(),
+            _permit: permit,
+        };
+        R::spawn(async move {
+            // The client went away or the connection was aborted; there is
+            // nobody to report either to
+            let _ = Abortable::new(connection.serve::<R>(stream, peer), registration).await;
+        });
+    }
+}
+
+// A connection's share of the server, released when its task ends
+struct Connection {
+    id: u64,
+    router: Arc<Router>,
+    config: Arc<ServerConfig>,
+    connections: Arc<Connections>,
+    _permit: SemaphoreGuardArc,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.connections.remove(self.id);
+    }
+}
+
+impl Connection {
+    async fn serve<R: Runtime>(self, mut stream: R::Stream, peer: SocketAddr) -> io::Result<()> {
+        // Blocking streams cannot be timed out from outside, so they get the
+        // idle timeout as socket timeout
+        stream.set_timeout(Some(self.config.idle_timeout))?;
+        let mut buf = BytesMut::new();
+        loop {
+            if !self.connections.set_busy(self.id, false) {
+                return Ok(());
+            }
+            let read = read_request(&mut stream, &mut buf, peer, &self.config);
+            let request = match timeout::<R, _>(self.config.idle_timeout, read).await {
+                Some(Ok(Some(request))) => request,
+                Some(Ok(None)) => return Ok(()),
+                Some(Err(ReadError::Reject(status))) => {
+                    return write_response(&mut stream, Response::new(status), false, true).await;
+                }
+                Some(Err(ReadError::Io(e))) if !is_socket_timeout(&e) => return Err(e),
+                // Nothing to answer when the client sent nothing at all
+                Some(Err(ReadError::Io(_))) | None if buf.is_empty() => return Ok(()),
+                Some(Err(ReadError::Io(_))) | None => {
+                    return write_response(&mut stream, Response::new(408), false, true).await;
+                }
+            };
+            self.connections.set_busy(self.id, true);
+            let head_only = request.method == Method::Head;
+            let keep_alive = request.keep_alive();
+            let response = self.router.handle(request).await;
+            // A shutdown may have started while the handler ran
+            let close = !keep_alive || self.connections.is_draining();
+            write_response(&mut stream, response, head_only, close).await?;
+            if close {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// The open connections of a running server, so a shutdown can close them
+#[derive(Default)]
+struct Connections {
+    inner: Mutex<ConnectionsInner>,
+}
+
+#[derive(Default)]
+struct ConnectionsInner {
+    draining: bool,
+    next_id: u64,
+    // Abort handle and whether a request is being handled
+    open: HashMap<u64, (AbortHandle, bool)>,
+}
+
+impl Connections {
+    fn add(&self, abort: AbortHandle) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.open.insert(id, (abort, false));
+        id
+    }
+
+    fn remove(&self, id: u64) {
+        self.inner.lock().unwrap().open.remove(&id);
+    }
+
+    // Records whether connection `id` is handling a request. Returns false
+    // once the server is draining and the connection should close.
+    fn set_busy(&self, id: u64, busy: bool) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some((_, state)) = inner.open.get_mut(&id) {
+            *state = busy;
+        }
+        !inner.draining
+    }
+
+    // Closes connections waiting for a request; busy ones close after
+    // their response
+    fn drain(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.draining = true;
+        for (abort, busy) in inner.open.values() {
+            if !busy {
+                abort.abort();
+            }
+        }
+    }
+
+    fn is_draining(&self) -> bool {
+        self.inner.lock().unwrap().draining
+    }
+
+    fn abort_all(&self) {
+        for (abort, _) in self.inner.lock().unwrap().open.values() {
+            abort.abort();
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().open.is_empty()
+    }
+}
+
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+    )
+}
@@ -0,0 +1,63 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+// How `RedisStore` turns values into the bytes stored under a key. Codecs
+// are zero-sized marker types, selected as a type parameter.
+pub trait Codec {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, BoxError>;
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, BoxError>;
+}
+
+// JSON text, readable with any Redis client
+pub struct Json;
+
+impl Codec for Json {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, BoxError> {
+        Ok(serde_json::to_vec(value)?)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, BoxError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+// Compact binary encoding with bincode's standard configuration
+#[cfg(feature = "bincode")]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl Codec for Bincode {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, BoxError> {
+        Ok(bincode::serde::encode_to_vec(
+            value,
+            bincode::config::standard(),
+        )?)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, BoxError> {
+        let (value, read) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+        if read != bytes.len() {
+            return Err(format!("{} trailing bytes", bytes.len() - read).into());
+        }
+        Ok(value)
+    }
+}
+
+// MessagePack with struct fields stored by name
+#[cfg(feature = "msgpack")]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MessagePack {
+    fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, BoxError> {
+        Ok(rmp_serde::to_vec_named(value)?)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, BoxError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
@@ -0,0 +1,106 @@
+use mini_redis::{
+    client::{connect, Client},
+    Result,
+};
+use std::io;
+use std::time::Duration;
+use url::Url;
+
+// Where and how to connect to Redis. Build it from a `redis://host:port/`
+// URL or from the environment, so the same code runs against any instance:
+//
+// - `REDIS_URL`: the server URL, `redis://127.0.0.1:6379/` when unset
+// - `REDIS_CONNECT_TIMEOUT_MS`: overrides `connect_timeout`
+// - `REDIS_CLIENT_NAME`: sets `client_name`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedisConfig {
+    pub host: String,
+    pub port: u16,
+    // `None` waits for the OS to give up
+    pub connect_timeout: Option<Duration>,
+    // Identifies this client in error messages. mini-redis has no
+    // `CLIENT SETNAME`, so the name is not sent to the server.
+    pub client_name: Option<String>,
+}
+
+const DEFAULT_PORT: u16 = 6379;
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: DEFAULT_PORT,
+            connect_timeout: Some(Duration::from_secs(5)),
+            client_name: None,
+        }
+    }
+}
+
+impl RedisConfig {
+    // Parses `redis://host[:port][/0]`. Credentials and databases other
+    // than 0 are rejected, as mini-redis supports neither.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let url = Url::parse(url)?;
+        if url.scheme() != "redis" {
+            return Err(format!("unsupported Redis URL scheme {:?}", url.scheme()).into());
+        }
+        let host = match url.host_str() {
+            Some(host) if !host.is_empty() => host.to_string(),
+            _ => return Err("Redis URL has no host".into()),
+        };
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err("Redis URL credentials are not supported".into());
+        }
+        if !matches!(url.path(), "" | "/" | "/0") {
+            return Err(format!("unsupported Redis database {:?}", url.path()).into());
+        }
+        Ok(Self {
+            host,
+            port: url.port().unwrap_or(DEFAULT_PORT),
+            ..Self::default()
+        })
+    }
+
+    // Reads the variables listed on `RedisConfig`
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    pub(crate) fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut config = match var("REDIS_URL") {
+            Some(url) => Self::from_url(&url)?,
+            None => Self::default(),
+        };
+        if let Some(ms) = var("REDIS_CONNECT_TIMEOUT_MS") {
+            let ms = ms
+                .parse()
+                .map_err(|_| format!("invalid REDIS_CONNECT_TIMEOUT_MS {:?}", ms))?;
+            config.connect_timeout = Some(Duration::from_millis(ms));
+        }
+        config.client_name = var("REDIS_CLIENT_NAME").or(config.client_name);
+        Ok(config)
+    }
+
+    // `host:port`, with IPv6 hosts in brackets
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    pub async fn connect(&self) -> Result<Client> {
+        let addr = self.addr();
+        let connected = match self.connect_timeout {
+            Some(limit) => tokio::time::timeout(limit, connect(addr.as_str()))
+                .await
+                .unwrap_or_else(|_| Err(io::Error::from(io::ErrorKind::TimedOut).into())),
+            None => connect(addr.as_str()).await,
+        };
+        connected.map_err(|e| match &self.client_name {
+            Some(name) => format!(
+                "Redis client {:?}: connecting to {} failed: {}",
+                name, addr, e
+            )
+            .into(),
+            None => format!("connecting to Redis at {} failed: {}", addr, e).into(),
+        })
+    }
+}
@@ -0,0 +1,87 @@
+use super::RedisConfig;
+use bytes::Bytes;
+use mini_redis::client::Client;
+use mini_redis::Result;
+use tokio::sync::{mpsc, oneshot};
+
+// Commands waiting for the manager task beyond this make senders wait
+const QUEUE_SIZE: usize = 32;
+
+// Shares one mini-redis `Client` among tasks. The client lives in a manager
+// task that runs commands one at a time as they arrive over a channel and
+// sends each result back over a oneshot channel. The task ends once every
+// handle has been dropped.
+#[derive(Debug, Clone)]
+pub struct RedisHandle {
+    commands: mpsc::Sender<Command>,
+}
+
+#[derive(Debug)]
+enum Command {
+    Get {
+        key: String,
+        reply: oneshot::Sender<Result<Option<Bytes>>>,
+    },
+    Set {
+        key: String,
+        value: Bytes,
+        reply: oneshot::Sender<Result<()>>,
+    },
+}
+
+impl RedisHandle {
+    pub async fn connect(config: &RedisConfig) -> Result<Self> {
+        Ok(Self::spawn(config.connect().await?))
+    }
+
+    // Moves `client` into a new manager task
+    pub fn spawn(client: Client) -> Self {
+        let (commands, receiver) = mpsc::channel(QUEUE_SIZE);
+        tokio::spawn(manage(client, receiver));
+        Self { commands }
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Get {
+            key: key.to_string(),
+            reply,
+        })
+        .await?;
+        response.await.map_err(|_| stopped())?
+    }
+
+    pub async fn set(&self, key: &str, value: Bytes) -> Result<()> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Set {
+            key: key.to_string(),
+            value,
+            reply,
+        })
+        .await?;
+        response.await.map_err(|_| stopped())?
+    }
+
+    async fn send(&self, command: Command) -> Result<()> {
+        self.commands.send(command).await.map_err(|_| stopped())
+    }
+}
+
+async fn manage(mut client: Client, mut commands: mpsc::Receiver<Command>) {
+    while let Some(command) = commands.recv().await {
+        // A caller that gave up waiting has dropped its receiver; the
+        // command ran anyway, so its result is simply discarded
+        match command {
+            Command::Get { key, reply } => {
+                let _ = reply.send(client.get(&key).await);
+            }
+            Command::Set { key, value, reply } => {
+                let _ = reply.send(client.set(&key, value).await);
+            }
+        }
+    }
+}
+
+fn stopped() -> mini_redis::Error {
+    "the Redis manager task has stopped".into()
+}
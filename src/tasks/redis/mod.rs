@@ -0,0 +1,37 @@
+use bytes::Bytes;
+
+use mini_redis::{
+    Result,
+    client::Client,
+};
+
+mod codec;
+mod config;
+mod handle;
+mod pool;
+mod server;
+mod store;
+
+#[cfg(feature = "bincode")]
+pub use codec::Bincode;
+#[cfg(feature = "msgpack")]
+pub use codec::MessagePack;
+pub use codec::{Codec, Json};
+pub use config::RedisConfig;
+pub use handle::RedisHandle;
+pub use pool::{PooledClient, RedisPool, RedisPoolConfig};
+pub use server::{spawn_server, ServerHandle};
+pub use store::{RedisStore, StoreError};
+
+// Connects to the server configured by the environment, see `RedisConfig`
+pub async fn init_client() -> Result<Client> {
+    RedisConfig::from_env()?.connect().await
+}
+
+pub async fn set(client: &mut Client, key: &str, value: Bytes) -> Result<()> {
+    client.set(key, value).await
+}
+
+pub async fn get(client: &mut Client, key: &str) -> Result<Option<Bytes>> {
+    client.get(key).await
+}
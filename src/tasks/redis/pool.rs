@@ -0,0 +1,144 @@
+use super::RedisConfig;
+use async_lock::{Semaphore, SemaphoreGuardArc};
+use mini_redis::client::Client;
+use mini_redis::Result;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Limits for a `RedisPool`
+#[derive(Debug, Clone)]
+pub struct RedisPoolConfig {
+    // Connections opened up front by `RedisPool::new`
+    pub min_size: usize,
+    // Connections checked out at once; further checkouts wait
+    pub max_size: usize,
+    // Idle connections must answer a probe within this time on checkout,
+    // else they are evicted. `None` skips the check.
+    pub health_check_timeout: Option<Duration>,
+}
+
+impl Default for RedisPoolConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 1,
+            max_size: 16,
+            health_check_timeout: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+// mini-redis has no PING command, so the probe is a GET of this key
+const HEALTH_CHECK_KEY: &str = "__redis_pool_health_check__";
+
+// A bounded pool of mini-redis connections shared by many tasks. Cloning is
+// cheap and clones share the pool.
+//
+//     let pool = RedisPool::new(RedisConfig::from_env()?, RedisPoolConfig::default()).await?;
+//     let mut client = pool.get().await?;
+//     set(&mut client, "hello", "world".into()).await?;
+#[derive(Clone)]
+pub struct RedisPool {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    config: RedisConfig,
+    pool: RedisPoolConfig,
+    idle: Mutex<VecDeque<Client>>,
+    permits: Arc<Semaphore>,
+}
+
+impl RedisPool {
+    pub async fn new(config: RedisConfig, pool: RedisPoolConfig) -> Result<Self> {
+        let mut idle = VecDeque::new();
+        for _ in 0..pool.min_size.min(pool.max_size) {
+            idle.push_back(config.connect().await?);
+        }
+        let permits = Arc::new(Semaphore::new(pool.max_size.max(1)));
+        Ok(Self {
+            shared: Arc::new(Shared {
+                config,
+                pool,
+                idle: Mutex::new(idle),
+                permits,
+            }),
+        })
+    }
+
+    // Waits for a free slot and returns a healthy idle connection, or a new
+    // one when none is left. Connections that fail the health check are
+    // dropped.
+    pub async fn get(&self) -> Result<PooledClient> {
+        let permit = self.shared.permits.acquire_arc().await;
+        loop {
+            let idle = self.shared.idle.lock().unwrap().pop_front();
+            let client = match idle {
+                Some(mut client) => match self.shared.pool.health_check_timeout {
+                    Some(limit) if !is_healthy(&mut client, limit).await => continue,
+                    _ => client,
+                },
+                None => self.shared.config.connect().await?,
+            };
+            return Ok(PooledClient {
+                client: Some(client),
+                broken: false,
+                shared: self.shared.clone(),
+                _permit: permit,
+            });
+        }
+    }
+
+    pub fn idle_count(&self) -> usize {
+        self.shared.idle.lock().unwrap().len()
+    }
+}
+
+async fn is_healthy(client: &mut Client, limit: Duration) -> bool {
+    matches!(
+        tokio::time::timeout(limit, client.get(HEALTH_CHECK_KEY)).await,
+        Ok(Ok(_))
+    )
+}
+
+// A connection checked out of a `RedisPool`. Dereferences to the mini-redis
+// `Client` and goes back to the pool when dropped, unless marked broken.
+pub struct PooledClient {
+    client: Option<Client>,
+    broken: bool,
+    shared: Arc<Shared>,
+    _permit: SemaphoreGuardArc,
+}
+
+impl PooledClient {
+    // Drops the connection instead of returning it to the pool, e.g. after
+    // an I/O error left it in an unknown state
+    pub fn mark_broken(&mut self) {
+        self.broken = true;
+    }
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().expect("present until dropped")
+    }
+}
+
+impl DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().expect("present until dropped")
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            if !self.broken {
+                self.shared.idle.lock().unwrap().push_back(client);
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use super::RedisConfig;
+use std::io;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+// An in-process mini-redis server on an ephemeral 127.0.0.1 port, so Redis
+// code can be tested without installing and starting a server. Its data
+// lives in memory and is gone after shutdown.
+//
+//     let (addr, server) = spawn_server().await?;
+//     let mut client = mini_redis::client::connect(addr).await?;
+//     ...
+//     server.shutdown().await;
+pub async fn spawn_server() -> io::Result<(SocketAddr, ServerHandle)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let (stop, stopped) = oneshot::channel::<()>();
+    let task = tokio::spawn(async move {
+        // Errors are logged by mini-redis itself
+        let _ = mini_redis::server::run(listener, stopped).await;
+    });
+    let handle = ServerHandle {
+        addr,
+        stop: Some(stop),
+        task,
+    };
+    Ok((addr, handle))
+}
+
+// Stops the server from `spawn_server`, when shut down explicitly or dropped
+#[derive(Debug)]
+pub struct ServerHandle {
+    addr: SocketAddr,
+    stop: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl ServerHandle {
+    // A config that connects to this server
+    pub fn config(&self) -> RedisConfig {
+        RedisConfig {
+            host: self.addr.ip().to_string(),
+            port: self.addr.port(),
+            ..RedisConfig::default()
+        }
+    }
+
+    // Stops accepting, closes the open connections and waits until done
+    pub async fn shutdown(mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
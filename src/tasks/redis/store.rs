@@ -0,0 +1,100 @@
+use super::codec::{Codec, Json};
+use super::RedisHandle;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+// Typed values on top of a `RedisHandle`. Values are serialized with a
+// `Codec`; `get_json`/`set_json` use JSON, `get_as`/`set_as` any codec:
+//
+//     store.set_json("user:1", &user).await?;
+//     let user: Option<User> = store.get_json("user:1").await?;
+#[derive(Debug, Clone)]
+pub struct RedisStore {
+    handle: RedisHandle,
+}
+
+// Why a `RedisStore` operation failed. A value that cannot be decoded is a
+// `Decode` error, never a `Redis` one, so callers can tell corrupt or
+// outdated data from an unreachable server.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StoreError {
+    // The command failed, e.g. the connection broke
+    Redis(mini_redis::Error),
+    // The value could not be serialized
+    Encode(Box<dyn Error + Send + Sync>),
+    // The stored bytes are not a valid value of the requested type
+    Decode {
+        key: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Redis(e) => write!(f, "Redis error: {}", e),
+            StoreError::Encode(e) => write!(f, "Cannot encode value: {}", e),
+            StoreError::Decode { key, source } => {
+                write!(f, "Cannot decode value of {:?}: {}", key, source)
+            }
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StoreError::Redis(e) | StoreError::Encode(e) => Some(e.as_ref()),
+            StoreError::Decode { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+impl RedisStore {
+    pub fn new(handle: RedisHandle) -> Self {
+        Self { handle }
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, StoreError> {
+        self.get_as::<Json, T>(key).await
+    }
+
+    pub async fn set_json<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), StoreError> {
+        self.set_as::<Json, T>(key, value).await
+    }
+
+    // The value under `key` decoded with codec `C`, `None` if there is none
+    pub async fn get_as<C: Codec, T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, StoreError> {
+        let Some(bytes) = self.handle.get(key).await.map_err(StoreError::Redis)? else {
+            return Ok(None);
+        };
+        C::decode(&bytes)
+            .map(Some)
+            .map_err(|source| StoreError::Decode {
+                key: key.to_string(),
+                source,
+            })
+    }
+
+    pub async fn set_as<C: Codec, T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), StoreError> {
+        let bytes = C::encode(value).map_err(StoreError::Encode)?;
+        self.handle
+            .set(key, bytes.into())
+            .await
+            .map_err(StoreError::Redis)
+    }
+}
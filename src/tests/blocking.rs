@@ -0,0 +1,225 @@
+use crate::runtime::{block_on, timeout, StdRuntime};
+use crate::simple_http_client::connector::connect_any;
+use crate::simple_http_client::{
+    BlockingClient, ClientConfig, HttpError, Method, SimpleHttpClient, StaticResolver, TimeoutKind,
+};
+use crate::tasks::http::{HttpBin, Request, Response, Router, Server, ServerConfig};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Answers `requests` requests on a single connection with `response` and
+// hands back the raw requests received
+fn serve(requests: usize, response: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        let mut buf = vec![0; 4096];
+        for _ in 0..requests {
+            let n = socket.read(&mut buf).unwrap();
+            received.push_str(&String::from_utf8_lossy(&buf[..n]));
+            socket.write_all(response.as_bytes()).unwrap();
+        }
+        received
+    });
+    (url, handle)
+}
+
+// A listener that never accepts, with its backlog filled up so that further
+// connects hang like those to an unreachable host. Keep the returned
+// streams alive for as long as connects should hang.
+fn unresponsive_listener() -> (TcpListener, Vec<TcpStream>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut queued = Vec::new();
+    while queued.len() < 10_000 {
+        match TcpStream::connect_timeout(&addr, Duration::from_millis(50)) {
+            Ok(stream) => queued.push(stream),
+            Err(_) => return (listener, queued),
+        }
+    }
+    panic!("connects to a full listen backlog did not hang");
+}
+
+#[test]
+fn blocking_get_and_post_from_plain_threads() -> Result<(), HttpError> {
+    let (url, server) = serve(2, "HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nfox");
+    let client = SimpleHttpClient::blocking();
+
+    let worker = {
+        let (client, url) = (client.clone(), url.clone());
+        thread::spawn(move || client.get(&format!("{}/get", url)))
+    };
+    assert_eq!(worker.join().unwrap()?.text()?, "fox");
+    let response = client.post(&format!("{}/post", url), "rabbit")?;
+    assert_eq!(response.text()?, "fox");
+
+    // Both requests went over the one connection the server accepts
+    let received = server.join().unwrap();
+    assert!(received.starts_with("GET /get HTTP/1.1\r\n"));
+    assert!(received.contains("POST /post HTTP/1.1\r\n"));
+    assert!(received.ends_with("\r\n\r\nrabbit"));
+    Ok(())
+}
+
+#[test]
+fn blocking_request_builder() -> Result<(), HttpError> {
+    let (url, server) = serve(1, "HTTP/1.1 204 No Content\r\n\r\n");
+    let response = BlockingClient::new()
+        .request(Method::Delete, &url)
+        .header("X-Animal", "fox")
+        .send_blocking()?;
+    assert_eq!(response.status, 204);
+    assert!(server.join().unwrap().contains("X-Animal: fox\r\n"));
+    Ok(())
+}
+
+#[test]
+fn blocking_read_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (socket, _) = listener.accept().unwrap();
+        thread::sleep(Duration::from_secs(5));
+        drop(socket);
+    });
+
+    let mut config = ClientConfig::default();
+    config.timeouts.read = Some(Duration::from_millis(50));
+    let started = Instant::now();
+    let error = BlockingClient::with_config(config).get(&url).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            HttpError::Timeout {
+                kind: TimeoutKind::Read,
+                ..
+            }
+        ),
+        "{:?}",
+        error
+    );
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn std_runtime_timer_does_not_block_other_futures() {
+    let pending = futures_util::future::pending::<()>();
+    assert_eq!(
+        block_on(timeout::<StdRuntime, _>(Duration::from_millis(20), pending)),
+        None
+    );
+    let ready = async { 7 };
+    assert_eq!(
+        block_on(timeout::<StdRuntime, _>(Duration::from_secs(60), ready)),
+        Some(7)
+    );
+}
+
+#[test]
+fn blocking_client_with_static_resolver_is_hermetic() -> Result<(), HttpError> {
+    let (url, server) = serve(1, "HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nfox");
+    let port = url.rsplit(':').next().unwrap();
+    let resolver = StaticResolver::new().with_host("httpbin.org", ["127.0.0.1".parse().unwrap()]);
+    let config = ClientConfig {
+        resolver: Some(Arc::new(resolver)),
+        ..ClientConfig::default()
+    };
+
+    let response =
+        BlockingClient::with_config(config).get(&format!("http://httpbin.org:{}/get", port))?;
+    assert_eq!(response.text()?, "fox");
+    let request = server.join().unwrap();
+    assert!(
+        request.contains(&format!("Host: httpbin.org:{}\r\n", port)),
+        "{}",
+        request
+    );
+    Ok(())
+}
+
+#[test]
+fn blocking_post_to_httpbin_on_std_runtime() -> Result<(), HttpError> {
+    let httpbin = block_on(HttpBin::start::<StdRuntime>())?;
+    let client = BlockingClient::new();
+    let response = client.post(&httpbin.url("/post?animal=fox"), "rabbit")?;
+    let text = response.text()?;
+    assert_eq!(response.status, 200);
+    assert!(text.contains(r#""data": "rabbit""#));
+    assert!(text.contains(r#""args": {"animal": "fox"}"#));
+
+    let response = client.get(&httpbin.url("/headers"))?;
+    assert!(response.text()?.contains(r#""Host": ""#));
+    Ok(())
+}
+
+#[test]
+fn blocking_connect_timeout() {
+    let (listener, _queued) = unresponsive_listener();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let mut config = ClientConfig::default();
+    config.timeouts.connect = Some(Duration::from_millis(100));
+    let started = Instant::now();
+    let error = BlockingClient::with_config(config).get(&url).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            HttpError::Timeout {
+                kind: TimeoutKind::Connect,
+                ..
+            }
+        ),
+        "{:?}",
+        error
+    );
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn std_connector_moves_on_after_attempt_delay() -> std::io::Result<()> {
+    let (hanging, _queued) = unresponsive_listener();
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let open = listener.local_addr()?;
+    let addrs: [SocketAddr; 2] = [hanging.local_addr()?, open];
+
+    let started = Instant::now();
+    let connect = connect_any::<StdRuntime>(&addrs, Duration::from_millis(50), None);
+    assert_eq!(block_on(connect)?.peer_addr()?, open);
+    assert!(started.elapsed() < Duration::from_secs(2));
+    Ok(())
+}
+
+#[test]
+fn std_server_closes_idle_connections_and_shuts_down() -> std::io::Result<()> {
+    let config = ServerConfig {
+        idle_timeout: Duration::from_millis(200),
+        max_connections: 1,
+        ..ServerConfig::default()
+    };
+    let router = Router::new().get("/", |_: Request| async { Response::text("ok") });
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let server = block_on(Server::<StdRuntime>::bind_with_config(addr, router, config))?;
+    let addr = server.local_addr()?;
+    let shutdown = server.shutdown_handle();
+    let running = thread::spawn(move || block_on(server.run()));
+
+    // A silent client is disconnected and gives its permit back
+    let mut idle = TcpStream::connect(addr)?;
+    idle.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let started = Instant::now();
+    assert_eq!(idle.read(&mut [0; 64])?, 0);
+    assert!(started.elapsed() < Duration::from_secs(2));
+    let response = BlockingClient::new().get(&format!("http://{}/", addr))?;
+    assert_eq!(response.text()?, "ok");
+
+    let started = Instant::now();
+    shutdown.shutdown();
+    running.join().unwrap()?;
+    assert!(started.elapsed() < Duration::from_secs(2));
+    Ok(())
+}
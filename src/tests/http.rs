@@ -0,0 +1,333 @@
+use crate::runtime::block_on;
+use crate::simple_http_client::connector::{interleave, unbracket};
+use crate::simple_http_client::reader::ChunkedDecoder;
+use crate::simple_http_client::request::Request;
+use crate::simple_http_client::target::request_target;
+use crate::simple_http_client::{
+    with_query, CachingResolver, HeaderMap, HttpResponse, Method, Resolver, StaticResolver,
+};
+use crate::tasks::http::files::{http_date, parse_http_date, parse_range};
+use bytes::BytesMut;
+use futures_util::future::BoxFuture;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+use url::Url;
+
+#[test]
+fn parse_response_status_headers_and_body() {
+    let raw = b"HTTP/1.1 404 Not Found\r\nContent-Type: text/html\r\nX-Trace: a\r\nx-trace: b\r\n\r\n<h1>missing</h1>";
+    let response = HttpResponse::parse(raw).unwrap();
+
+    assert_eq!(response.version, "HTTP/1.1");
+    assert_eq!(response.status, 404);
+    assert_eq!(response.reason, "Not Found");
+    assert!(response.is_client_error());
+    assert!(!response.is_success());
+    assert_eq!(response.content_type(), Some("text/html"));
+    assert_eq!(
+        response.headers.get_all("X-TRACE").collect::<Vec<_>>(),
+        vec!["a", "b"]
+    );
+    assert_eq!(&response.body[..], b"<h1>missing</h1>");
+}
+
+#[test]
+fn parse_response_without_reason_phrase() {
+    let response = HttpResponse::parse(b"HTTP/1.1 500\r\n\r\n").unwrap();
+    assert_eq!(response.status, 500);
+    assert_eq!(response.reason, "");
+    assert!(response.is_server_error());
+    assert!(response.body.is_empty());
+}
+
+#[test]
+fn parse_response_rejects_garbage() {
+    assert!(HttpResponse::parse(b"hello world").is_err());
+    assert!(HttpResponse::parse(b"HTTP/1.1 abc OK\r\n\r\n").is_err());
+    assert!(HttpResponse::parse(b"HTTP/1.1 200 OK\r\nbroken header\r\n\r\n").is_err());
+}
+
+#[test]
+fn header_map_is_case_insensitive() {
+    let mut headers = HeaderMap::new();
+    headers.append("Set-Cookie", "a=1");
+    headers.append("set-cookie", "b=2");
+    assert_eq!(headers.len(), 2);
+
+    headers.insert("SET-COOKIE", "c=3");
+    assert_eq!(headers.get("Set-Cookie"), Some("c=3"));
+    assert_eq!(headers.len(), 1);
+
+    headers.remove("set-COOKIE");
+    assert!(headers.is_empty());
+}
+
+#[test]
+fn request_target_keeps_query_and_drops_fragment() {
+    let url = Url::parse("http://example.com/search?q=lion&page=2#results").unwrap();
+    assert_eq!(request_target(&url), "/search?q=lion&page=2");
+
+    let url = Url::parse("http://example.com").unwrap();
+    assert_eq!(request_target(&url), "/");
+}
+
+#[test]
+fn request_target_is_percent_encoded() {
+    let url = Url::parse("http://example.com/big cats/ü?name=snow leopard").unwrap();
+    assert_eq!(
+        request_target(&url),
+        "/big%20cats/%C3%BC?name=snow%20leopard"
+    );
+}
+
+#[test]
+fn with_query_appends_encoded_pairs() {
+    let url = with_query(
+        "http://example.com/search?sort=asc#top",
+        [("q", "lion & tiger"), ("lang", "en")],
+    )
+    .unwrap();
+    assert_eq!(
+        request_target(&url),
+        "/search?sort=asc&q=lion+%26+tiger&lang=en"
+    );
+}
+
+#[test]
+fn parse_response_honours_content_length() {
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello, and then some";
+    let response = HttpResponse::parse(raw).unwrap();
+    assert_eq!(&response.body[..], b"hello");
+
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 50\r\n\r\nhello";
+    assert!(HttpResponse::parse(raw).is_err());
+}
+
+#[test]
+fn parse_response_decodes_chunked_body_and_trailers() {
+    let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+        4\r\nWiki\r\n6;ext=1\r\npedia \r\nE\r\nin \r\n\r\nchunks.\r\n0\r\nExpires: never\r\n\r\n";
+    let response = HttpResponse::parse(raw).unwrap();
+    assert_eq!(&response.body[..], b"Wikipedia in \r\n\r\nchunks.");
+    assert_eq!(response.trailers.get("expires"), Some("never"));
+}
+
+#[test]
+fn parse_response_without_body_for_204_and_304() {
+    let raw = b"HTTP/1.1 304 Not Modified\r\nContent-Length: 10\r\n\r\n";
+    let response = HttpResponse::parse(raw).unwrap();
+    assert!(response.body.is_empty());
+}
+
+#[test]
+fn chunked_decoder_resumes_across_partial_input() {
+    let wire = b"5\r\nhello\r\n7\r\n, world\r\n0\r\n\r\n";
+    let mut decoder = ChunkedDecoder::default();
+    let mut input = BytesMut::new();
+    let mut output = BytesMut::new();
+
+    // Feed the body one byte at a time, as a slow server would
+    let mut done = false;
+    for byte in wire {
+        assert!(!done);
+        input.extend_from_slice(&[*byte]);
+        done = decoder.decode(&mut input, &mut output).unwrap();
+    }
+    assert!(done);
+    assert_eq!(&output[..], b"hello, world");
+}
+
+#[test]
+fn chunked_decoder_rejects_bad_sizes() {
+    let mut decoder = ChunkedDecoder::default();
+    let mut input = BytesMut::from(&b"zz\r\nhello\r\n"[..]);
+    assert!(decoder.decode(&mut input, &mut BytesMut::new()).is_err());
+}
+
+#[test]
+fn request_serializes_method_headers_and_body() {
+    let url = Url::parse("http://example.com:8080/animals/7?full=1").unwrap();
+    let mut request = Request::new(Method::Put, url);
+    request.headers.append("X-Zoo", "north");
+    request.headers.append("content-length", "999");
+    request.body = "🦁".into();
+
+    let wire = String::from_utf8(request.to_bytes()).unwrap();
+    assert_eq!(
+        wire,
+        "PUT /animals/7?full=1 HTTP/1.1\r\nHost: example.com:8080\r\nX-Zoo: north\r\nContent-Length: 4\r\n\r\n🦁"
+    );
+}
+
+#[test]
+fn request_without_body() {
+    let url = Url::parse("https://example.com/").unwrap();
+    let wire = Request::new(Method::Delete, url.clone()).to_bytes();
+    assert_eq!(wire, b"DELETE / HTTP/1.1\r\nHost: example.com\r\n\r\n");
+
+    // POST announces an empty body so servers do not wait for one
+    let wire = Request::new(Method::Post, url).to_bytes();
+    assert_eq!(
+        wire,
+        b"POST / HTTP/1.1\r\nHost: example.com\r\nContent-Length: 0\r\n\r\n"
+    );
+}
+
+#[test]
+fn method_idempotency() {
+    assert!(Method::Get.is_idempotent());
+    assert!(Method::Put.is_idempotent());
+    assert!(Method::Delete.is_idempotent());
+    assert!(!Method::Post.is_idempotent());
+    assert!(!Method::Patch.is_idempotent());
+}
+
+#[test]
+fn text_decodes_charset_from_content_type() {
+    // "Löwe" in ISO-8859-1
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=\"ISO-8859-1\"\r\n\r\nL\xf6we";
+    let response = HttpResponse::parse(raw).unwrap();
+    assert_eq!(response.charset(), Some("ISO-8859-1"));
+    assert_eq!(response.text().unwrap(), "Löwe");
+}
+
+#[test]
+fn text_defaults_to_utf8_and_rejects_invalid_bytes() {
+    let raw = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\r\nLöwe 🦁";
+    let response = HttpResponse::parse(raw.as_bytes()).unwrap();
+    assert_eq!(response.charset(), None);
+    assert_eq!(response.text().unwrap(), "Löwe 🦁");
+
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\r\nL\xf6we";
+    let response = HttpResponse::parse(raw).unwrap();
+    assert!(response.text().is_err());
+
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=klingon\r\n\r\nqapla'";
+    assert!(HttpResponse::parse(raw).unwrap().text().is_err());
+}
+
+#[test]
+fn bytes_are_lossless_for_binary_bodies() {
+    let mut raw =
+        b"HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: 256\r\n\r\n".to_vec();
+    let body: Vec<u8> = (0..=255).collect();
+    raw.extend_from_slice(&body);
+
+    let response = HttpResponse::parse(&raw).unwrap();
+    assert_eq!(response.bytes(), body);
+}
+
+#[test]
+fn interleave_alternates_address_families() {
+    let addrs: Vec<SocketAddr> = [
+        "[::1]:80",
+        "[::2]:80",
+        "[::3]:80",
+        "10.0.0.1:80",
+        "10.0.0.2:80",
+    ]
+    .iter()
+    .map(|addr| addr.parse().unwrap())
+    .collect();
+    let ordered: Vec<String> = interleave(addrs).iter().map(|a| a.to_string()).collect();
+    assert_eq!(
+        ordered,
+        [
+            "[::1]:80",
+            "10.0.0.1:80",
+            "[::2]:80",
+            "10.0.0.2:80",
+            "[::3]:80"
+        ]
+    );
+}
+
+#[test]
+fn unbracket_ipv6_literals_only() {
+    assert_eq!(unbracket("[::1]"), "::1");
+    assert_eq!(unbracket("example.com"), "example.com");
+    assert_eq!(unbracket("127.0.0.1"), "127.0.0.1");
+}
+
+// Resolves every host to 127.0.0.1 and counts the lookups
+#[derive(Debug, Default)]
+struct CountingResolver {
+    lookups: AtomicUsize,
+}
+
+impl Resolver for CountingResolver {
+    fn resolve<'a>(
+        &'a self,
+        _host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        Box::pin(async move { Ok(vec![addr]) })
+    }
+}
+
+#[test]
+fn static_resolver_overrides_hosts_case_insensitively() {
+    let resolver = StaticResolver::new().with_host("HttpBin.org", ["::1".parse().unwrap()]);
+    let addrs = block_on(resolver.resolve("httpbin.org", 8080)).unwrap();
+    assert_eq!(addrs, ["[::1]:8080".parse().unwrap()]);
+
+    let error = block_on(resolver.resolve("example.com", 80)).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+    let resolver = resolver.with_fallback(CountingResolver::default());
+    let addrs = block_on(resolver.resolve("example.com", 80)).unwrap();
+    assert_eq!(addrs, ["127.0.0.1:80".parse().unwrap()]);
+}
+
+#[test]
+fn caching_resolver_reuses_answers_until_ttl_expires() {
+    let resolver = CachingResolver::new(CountingResolver::default(), Duration::from_millis(50));
+    let lookups = |resolver: &CachingResolver<CountingResolver>| {
+        resolver.inner().lookups.load(Ordering::SeqCst)
+    };
+
+    block_on(resolver.resolve("fox.test", 80)).unwrap();
+    block_on(resolver.resolve("FOX.test", 80)).unwrap();
+    assert_eq!(lookups(&resolver), 1);
+    // Another port is another entry
+    block_on(resolver.resolve("fox.test", 81)).unwrap();
+    assert_eq!(lookups(&resolver), 2);
+
+    std::thread::sleep(Duration::from_millis(60));
+    block_on(resolver.resolve("fox.test", 80)).unwrap();
+    assert_eq!(lookups(&resolver), 3);
+}
+
+#[test]
+fn parse_byte_ranges() {
+    assert_eq!(parse_range("bytes=0-3", 10), Some(Ok((0, 3))));
+    assert_eq!(parse_range("bytes=4-", 10), Some(Ok((4, 9))));
+    assert_eq!(parse_range("bytes=-3", 10), Some(Ok((7, 9))));
+    assert_eq!(parse_range("bytes=5-100", 10), Some(Ok((5, 9))));
+    assert_eq!(parse_range("bytes=-100", 10), Some(Ok((0, 9))));
+    assert_eq!(parse_range("bytes=10-", 10), Some(Err(())));
+    assert_eq!(parse_range("bytes=-0", 10), Some(Err(())));
+    // Malformed and multiple ranges are ignored
+    assert_eq!(parse_range("bytes=3-1", 10), None);
+    assert_eq!(parse_range("bytes=0-1,4-5", 10), None);
+    assert_eq!(parse_range("lines=0-1", 10), None);
+}
+
+#[test]
+fn http_dates_round_trip() {
+    let time = UNIX_EPOCH + Duration::from_secs(784111777);
+    assert_eq!(http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+    assert_eq!(
+        parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+        Some(784111777)
+    );
+    assert_eq!(
+        http_date(UNIX_EPOCH + Duration::from_secs(951782400)),
+        "Tue, 29 Feb 2000 00:00:00 GMT"
+    );
+    assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+}
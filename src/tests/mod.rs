@@ -1,5 +1,11 @@
+mod blocking;
+mod http;
+
 #[cfg(feature = "smol")]
 mod smol;
 
+#[cfg(all(feature = "tls", feature = "tokio"))]
+mod tls;
+
 #[cfg(feature = "tokio")]
 mod tokio;
@@ -1,13 +1,212 @@
-use crate::simple_http_client::SimpleHttpClient;
+use crate::runtime::SmolRuntime;
+use crate::simple_http_client::{
+    HttpClient, HttpError, Method, RetryPolicy, SimpleHttpClient, TimeoutKind,
+};
+use crate::tasks::http::{HttpBin, Request, Response, Router, Server};
+use futures_util::StreamExt;
+use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::net::TcpListener;
 use std::io::Result;
+use std::time::Duration;
 
 #[test]
 fn smol_http_post() -> Result<()> {
     smol::block_on(async {
+        let httpbin = HttpBin::start::<SmolRuntime>().await?;
         let test_body = "Hello from World!";
-        let response = SimpleHttpClient::post("http://httpbin.org/post", test_body).await?;
-        println!("POST response: {}", response);
-        assert!(response.contains(test_body));
+        let response =
+            SimpleHttpClient::post_with::<SmolRuntime>(&httpbin.url("/post"), test_body).await?;
+        println!("POST response: {}", response.text()?);
+        assert_eq!(response.status, 200);
+        assert!(response.text()?.contains(test_body));
+        Ok(())
+    })
+}
+
+#[test]
+fn smol_runtime_get() -> Result<()> {
+    smol::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/get?animal=fox", listener.local_addr()?);
+        let server = smol::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nX-Animal: fox\r\n\r\nfox")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+
+        let response = SimpleHttpClient::get_with::<SmolRuntime>(&url).await?;
+
+        assert!(server.await.starts_with("GET /get?animal=fox HTTP/1.1\r\n"));
+        assert_eq!(response.status, 200);
+        assert_eq!(response.headers.get("x-animal"), Some("fox"));
+        assert_eq!(response.text()?, "fox");
+        Ok(())
+    })
+}
+
+#[test]
+fn smol_client_reuses_keep_alive_connections() -> Result<()> {
+    smol::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let server = smol::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            for _ in 0..2 {
+                socket.read(&mut buf).await.unwrap();
+                socket
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nfox")
+                    .await
+                    .unwrap();
+            }
+            // A second connection attempt would block here forever
+        });
+
+        let client = HttpClient::<SmolRuntime>::default();
+        assert_eq!(client.get(&url).await?.text()?, "fox");
+        assert_eq!(client.get(&url).await?.text()?, "fox");
+        assert_eq!(client.idle_connections(&url)?, 1);
+        server.await;
+        Ok(())
+    })
+}
+
+#[test]
+fn smol_streaming_body() -> Result<()> {
+    smol::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        smol::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\nfox")
+                .await
+                .unwrap();
+            smol::Timer::after(Duration::from_millis(20)).await;
+            socket.write_all(b"rabbit").await.unwrap();
+        })
+        .detach();
+
+        let response = HttpClient::<SmolRuntime>::default()
+            .request(Method::Get, &url)
+            .send_streaming()
+            .await?;
+        let chunks: Vec<_> = response.body.collect().await;
+        let body: Vec<u8> = chunks
+            .into_iter()
+            .collect::<std::result::Result<Vec<_>, _>>()?
+            .concat();
+        assert_eq!(body, b"foxrabbit");
+        Ok(())
+    })
+}
+
+#[test]
+fn smol_read_timeout_on_silent_server() -> Result<()> {
+    smol::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        smol::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let _ = socket.read(&mut buf).await;
+            smol::Timer::after(Duration::from_secs(60)).await;
+        })
+        .detach();
+
+        let error = HttpClient::<SmolRuntime>::default()
+            .request(Method::Get, &url)
+            .read_timeout(Duration::from_millis(50))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                HttpError::Timeout {
+                    kind: TimeoutKind::Read,
+                    ..
+                }
+            ),
+            "{:?}",
+            error
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn smol_retries_with_backoff_on_smol_timer() -> Result<()> {
+    smol::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        smol::spawn(async move {
+            // The first connection is closed without an answer
+            let (socket, _) = listener.accept().await.unwrap();
+            drop(socket);
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nfox")
+                .await
+                .unwrap();
+        })
+        .detach();
+
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+        let response = HttpClient::<SmolRuntime>::default()
+            .request(Method::Get, &url)
+            .retry(policy)
+            .send()
+            .await?;
+        assert_eq!(response.text()?, "fox");
+        Ok(())
+    })
+}
+
+#[test]
+fn smol_server_routes_with_path_params() -> Result<()> {
+    smol::block_on(async {
+        let router = Router::new().get("/animals/{name}", |request: Request| async move {
+            Response::text(format!("hello {}", request.param("name").unwrap()))
+        });
+        let server = Server::<SmolRuntime>::bind("127.0.0.1:0".parse().unwrap(), router).await?;
+        let url = format!("http://{}/animals/fox", server.local_addr()?);
+        smol::spawn(server.run()).detach();
+
+        let response = SimpleHttpClient::get_with::<SmolRuntime>(&url).await?;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text()?, "hello fox");
+        Ok(())
+    })
+}
+
+#[test]
+fn smol_server_serves_repo_index_html() -> Result<()> {
+    smol::block_on(async {
+        let router = Router::new().static_dir::<SmolRuntime>("/", env!("CARGO_MANIFEST_DIR"));
+        let server = Server::<SmolRuntime>::bind("127.0.0.1:0".parse().unwrap(), router).await?;
+        let url = format!("http://{}/", server.local_addr()?);
+        smol::spawn(server.run()).detach();
+
+        let response = SimpleHttpClient::get_with::<SmolRuntime>(&url).await?;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type(), Some("text/html; charset=utf-8"));
+        assert_eq!(
+            response.bytes(),
+            std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/index.html"))?
+        );
         Ok(())
     })
 }
@@ -0,0 +1,90 @@
+use crate::runtime::TokioRuntime;
+use crate::simple_http_client::{ClientConfig, HttpClient, HttpError, TlsConfig};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+// Starts a blocking HTTPS server for "localhost" with a freshly generated
+// self-signed certificate. Returns its base URL, the certificate as PEM and
+// a counter of accepted connections.
+fn serve_https() -> (String, String, Arc<AtomicUsize>) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_pem = cert.cert.pem();
+    let cert_der = CertificateDer::from(cert.cert.der().to_vec());
+    let key_der = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()));
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der], key_der)
+        .unwrap();
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "https://localhost:{}",
+        listener.local_addr().unwrap().port()
+    );
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let counter = accepted.clone();
+
+    thread::spawn(move || {
+        for socket in listener.incoming() {
+            let Ok(socket) = socket else { break };
+            counter.fetch_add(1, Ordering::SeqCst);
+            let conn = ServerConnection::new(config.clone()).unwrap();
+            thread::spawn(move || {
+                let mut tls = StreamOwned::new(conn, socket);
+                let mut buf = [0u8; 4096];
+                // Answer requests until the client goes away
+                while let Ok(n) = tls.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                    let response = b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nsecret";
+                    if tls.write_all(response).and_then(|_| tls.flush()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    (url, cert_pem, accepted)
+}
+
+#[tokio::test]
+async fn tls_get_with_custom_root_certificate() -> std::io::Result<()> {
+    let (url, cert_pem, accepted) = serve_https();
+    let mut tls = TlsConfig::empty();
+    assert_eq!(tls.add_root_certificates_pem(cert_pem.as_bytes())?, 1);
+    let client = HttpClient::<TokioRuntime>::with_config(ClientConfig {
+        tls,
+        ..ClientConfig::default()
+    });
+
+    for _ in 0..2 {
+        let response = client.get(&url).await?;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text()?, "secret");
+    }
+
+    // The TLS session is pooled and reused like a plain connection
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn tls_rejects_untrusted_certificate() {
+    let (url, _, _) = serve_https();
+    let client = HttpClient::<TokioRuntime>::default();
+
+    let error = client.get(&url).await.unwrap_err();
+    assert!(matches!(error, HttpError::Tls(_)), "{:?}", error);
+}